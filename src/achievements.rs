@@ -0,0 +1,162 @@
+use {
+    crate::{
+        app::GameMode,
+        balloons::BalloonKind,
+        difficulty::Difficulty,
+        events::{GameEvent, MessageLog, Subscriber},
+        utils
+    },
+    serde::{Deserialize, Serialize},
+    std::{cell::RefCell, collections::BTreeMap, rc::Rc}
+};
+
+pub const ACHIEVEMENTS_FILE: &str = "achievements.json";
+
+const POPS_GOAL: u64 = 10_000;
+const TOWERS_GOAL: u64 = 100;
+const HARD_WAVE: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    FirstPop,
+    BlimpBuster,
+    PopTenThousand,
+    Architect,
+    Victorious,
+    Flawless,
+    HardVictory,
+    HardWaveFifty
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 8] = [
+        Achievement::FirstPop,
+        Achievement::BlimpBuster,
+        Achievement::PopTenThousand,
+        Achievement::Architect,
+        Achievement::Victorious,
+        Achievement::Flawless,
+        Achievement::HardVictory,
+        Achievement::HardWaveFifty
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Achievement::FirstPop => "first pop",
+            Achievement::BlimpBuster => "blimp buster",
+            Achievement::PopTenThousand => "pop culture",
+            Achievement::Architect => "architect",
+            Achievement::Victorious => "victorious",
+            Achievement::Flawless => "flawless",
+            Achievement::HardVictory => "hardened",
+            Achievement::HardWaveFifty => "marathon"
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::FirstPop => "pop a balloon",
+            Achievement::BlimpBuster => "pop a blimp",
+            Achievement::PopTenThousand => "pop 10,000 balloons",
+            Achievement::Architect => "build 100 towers",
+            Achievement::Victorious => "win a game",
+            Achievement::Flawless => "win without losing a life",
+            Achievement::HardVictory => "win on hard or harder",
+            Achievement::HardWaveFifty => "reach wave 50 on hard or harder"
+        }
+    }
+}
+
+// what has been unlocked and the counters that add up over all games, kept in the profile directory
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AchievementStore {
+    pub unlocked: BTreeMap<Achievement, String>, // with the date it was unlocked at
+    pub popped: u64,
+    pub towers_built: u64
+}
+
+impl AchievementStore {
+    // how far along an achievement that counts something is, None for the others
+    pub fn progress(&self, achievement: Achievement) -> Option<(u64, u64)> {
+        match achievement {
+            Achievement::PopTenThousand => Some((self.popped.min(POPS_GOAL), POPS_GOAL)),
+            Achievement::Architect => Some((self.towers_built.min(TOWERS_GOAL), TOWERS_GOAL)),
+            _ => None
+        }
+    }
+}
+
+// follows one game, sandbox games and games that skipped the early waves don't count
+#[derive(Debug)]
+pub struct AchievementTracker {
+    pub store: AchievementStore,
+    difficulty: Difficulty,
+    counts: bool,
+    lives_lost: u64,
+    log: Rc<RefCell<MessageLog>> // where unlocks are announced
+}
+
+impl AchievementTracker {
+    // `lives_lost` is what a continued game had already lost before it was loaded
+    pub fn new(store: AchievementStore, difficulty: Difficulty, mode: GameMode, start_wave: usize, lives_lost: u64, log: Rc<RefCell<MessageLog>>) -> Self {
+        Self {
+            store,
+            difficulty,
+            counts: mode == GameMode::Normal && start_wave <= 1,
+            lives_lost,
+            log
+        }
+    }
+
+    fn unlock(&mut self, tick: u64, achievement: Achievement) {
+        if self.store.unlocked.contains_key(&achievement) {
+            return;
+        }
+        self.store.unlocked.insert(achievement, utils::today());
+        self.log.borrow_mut().announce(tick, format!("achievement unlocked: {}", achievement.name()));
+    }
+
+    fn hard(&self) -> bool {
+        matches!(self.difficulty, Difficulty::Hard | Difficulty::Impossible)
+    }
+}
+
+impl Subscriber for AchievementTracker {
+    fn notify(&mut self, tick: u64, event: &GameEvent) {
+        if !self.counts {
+            return;
+        }
+        match event {
+            GameEvent::BalloonPopped { kind, .. } => {
+                self.store.popped += 1;
+                self.unlock(tick, Achievement::FirstPop);
+                if *kind == BalloonKind::Blimp {
+                    self.unlock(tick, Achievement::BlimpBuster);
+                }
+                if self.store.popped >= POPS_GOAL {
+                    self.unlock(tick, Achievement::PopTenThousand);
+                }
+            },
+            GameEvent::TowerBuilt { .. } => {
+                self.store.towers_built += 1;
+                if self.store.towers_built >= TOWERS_GOAL {
+                    self.unlock(tick, Achievement::Architect);
+                }
+            },
+            GameEvent::BalloonLeaked { lives, .. } => self.lives_lost += *lives as u64,
+            GameEvent::WaveStarted { wave } if *wave >= HARD_WAVE && self.hard() => self.unlock(tick, Achievement::HardWaveFifty),
+            GameEvent::Victory { .. } => {
+                self.unlock(tick, Achievement::Victorious);
+                if self.lives_lost == 0 {
+                    self.unlock(tick, Achievement::Flawless);
+                }
+                if self.hard() {
+                    self.unlock(tick, Achievement::HardVictory);
+                }
+            },
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use {
+    crate::{balloons::BalloonKind, towers::TowerKind},
+    serde::{Deserialize, Serialize}
+};
+
+// everything the player can do that changes the simulation, these are what replays record
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    PlaceTower { kind: TowerKind, x: f64, y: f64 },
+    Upgrade { tower: usize, path: usize },
+    Sell { tower: usize },
+    StartWave,
+    SetAutoStart { enabled: bool },
+    SpawnBalloon { kind: BalloonKind },
+    SpawnWave,
+    Freeplay // keeps playing after the final wave was won
+}
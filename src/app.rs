@@ -1,9 +1,24 @@
 use {
     crate::{
-        balloons::*, towers::*, tui
+        action::Action,
+        balloons::*,
+        difficulty::Difficulty,
+        events::{EventBus, GameEvent, MessageLog},
+        hit_test::{world_point, HitMap, Target},
+        indicators::Indicators,
+        keymap::{KeyCommand, Keymap},
+        layout::{Panels, Screen},
+        maps::{Map, Segment},
+        observation::{Area, BalloonState, Observation, PathState, ShopEntry, TowerState},
+        replay::{Replay, SaveGame},
+        telemetry::{Telemetry, WaveTelemetry},
+        theme::Theme,
+        towers::*,
+        tui
     }, 
+    clap::ValueEnum,
     color_eyre::{
-        eyre::WrapErr, Result
+        eyre::{eyre, WrapErr}, Result
     }, 
     crossterm::{
         event::{
@@ -15,8 +30,7 @@ use {
             MouseButton,
             MouseEvent,
             MouseEventKind
-        },
-        terminal::size
+        }
     }, 
     ratatui::{
         prelude::{
@@ -28,38 +42,188 @@ use {
             Stylize, 
             Widget
         }, 
-            style::Color, 
             widgets::{
                 block::{Block, Position, Title}, 
                 canvas::{self, Canvas, Circle, Rectangle}, 
                 Borders, 
+                Clear,
                 Paragraph
             }
     }, 
-    std::time::Duration
+    serde::{Deserialize, Serialize},
+    std::{cell::{Ref, RefCell}, collections::VecDeque, path::PathBuf, rc::Rc, time::Duration}
 };
 
+const CURSOR_STEP: f64 = 2.5;
+const GAME_OVER_KEYS: [KeyCommand; 4] = [KeyCommand::Quit, KeyCommand::Restart, KeyCommand::Menu, KeyCommand::Freeplay];
+const PAUSE_KEYS: [KeyCommand; 6] = [KeyCommand::Pause, KeyCommand::Quit, KeyCommand::Up, KeyCommand::Down, KeyCommand::Select, KeyCommand::Step];
+const GAME_KEYS: [KeyCommand; 23] = [
+    KeyCommand::Quit, KeyCommand::Pause, KeyCommand::Restart, KeyCommand::Sell,
+    KeyCommand::Left, KeyCommand::Right, KeyCommand::Up, KeyCommand::Down,
+    KeyCommand::FastLeft, KeyCommand::FastRight, KeyCommand::FastUp, KeyCommand::FastDown,
+    KeyCommand::SpawnPanel, KeyCommand::SpawnWave, KeyCommand::Select, KeyCommand::Upgrades, KeyCommand::Cancel,
+    KeyCommand::Shop, KeyCommand::SpeedUp, KeyCommand::SpeedDown, KeyCommand::StartWave, KeyCommand::AutoStart, KeyCommand::Log
+];
+const MAX_SPEED: u8 = 3;
+const LOG_ROWS: u16 = 6;
+
 #[derive(Debug, Default)]
 pub struct App {
     pub score: u64,
     pub highscore: u64,
+    pub round: usize,
     exit: bool,
+    to_menu: bool,
     on_pause: bool,
+    pause_selected: usize,
+    notice: Option<String>, // shown in the pause menu, like whether saving worked
+    pub save_path: Option<PathBuf>, // where the pause menu saves to, saving is disabled without it
+    new_highscore: bool,
+    ranked: bool, // whether the game can set a highscore, not while skipping early waves or watching a replay
     dead: bool,
+    won: bool, // the final wave was survived, the victory screen is shown until the player decides how to go on
+    victory_reached: bool, // unlike won it stays set during freeplay
+    final_wave: Option<usize>,
+    freeplay: bool,
+    pub freeplay_score: u64, // scored after the victory, kept apart so that it doesn't count for the highscore
     path: BalloonPath,
     balloons: Vec<Balloon>,
     towers: Vec<Tower>,
     balloon_factory: BalloonFactory,
-    round: usize,
-    max_cols: u16,
-    max_rows: u16,
     gold: u16,
     hitpoints: u16,
     new_tower: Option<Tower>,
     tower_shop: TowerShop,
     upgrade_shop_open: Option<usize>,
     tower_shop_open: bool,
-    restart: bool
+    restart: bool,
+    pub sell_refund: f64,
+    sold_towers: Vec<(&'static str, TowerStats)>,
+    cursor: (f64, f64),
+    show_cursor: bool,
+    wave: BalloonWave,
+    wave_complete: bool,
+    speed: u8, // ticks per frame
+    step_requested: bool,
+    pub wave_settings: WaveSettings,
+    build_ticks_left: Option<u32>, // Some while the player is building between waves
+    pub difficulty: Difficulty,
+    pub mode: GameMode,
+    spawn_panel_open: bool,
+    pub seed: u64,
+    pub map: String,
+    ticks: u64,
+    pub replay: Replay, // what happened so far, saved after the game
+    playback: Option<VecDeque<(u64, Action)>>, // Some while watching a replay, the player can't act then
+    leaked: u64,
+    telemetry: Rc<RefCell<Telemetry>>, // fed by the events
+    indicators: Indicators,
+    pub show_health_bars: bool,
+    pub show_damage: bool, // floating damage numbers and pops
+    pub theme: Theme,
+    pub keymap: Keymap,
+    confirming: Option<KeyCommand>, // a restart or quit that waits for the player to confirm it
+    pub events: EventBus,
+    log: Rc<RefCell<MessageLog>>,
+    log_open: bool,
+    hit_map: HitMap // what is where on the last frame
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSettings {
+    pub difficulty: Difficulty,
+    pub mode: GameMode,
+    pub seed: u64,
+    pub map: Map,
+    pub start_wave: usize,
+    #[serde(default)]
+    pub waves: Vec<WaveDefinition>,
+    #[serde(default)]
+    pub wave_settings: WaveSettings,
+    #[serde(default = "default_sell_refund")]
+    pub sell_refund: f64 // share of what went into a tower that selling it gives back
+}
+
+fn default_sell_refund() -> f64 {
+    0.7
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::default(),
+            mode: GameMode::default(),
+            seed: 0,
+            map: Map::classic(),
+            start_wave: 1,
+            waves: vec![],
+            wave_settings: WaveSettings::default(),
+            sell_refund: default_sell_refund()
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GameMode {
+    #[default]
+    Normal,
+    Sandbox // infinite gold and lives, waves and balloons are spawned by hand
+}
+
+impl GameMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Normal => "normal",
+            GameMode::Sandbox => "sandbox"
+        }
+    }
+}
+
+// how a game ended, what comes next is up to the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Restart,
+    Menu,
+    Quit
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PauseItem {
+    Resume,
+    Restart,
+    Save,
+    Quit
+}
+
+impl PauseItem {
+    const ALL: [PauseItem; 4] = [PauseItem::Resume, PauseItem::Restart, PauseItem::Save, PauseItem::Quit];
+
+    fn label(&self) -> &'static str {
+        match self {
+            PauseItem::Resume => "resume",
+            PauseItem::Restart => "restart",
+            PauseItem::Save => "save",
+            PauseItem::Quit => "quit to menu"
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WaveSettings {
+    pub build_ticks: u32,
+    pub auto_start: bool,
+    pub early_bonus: u16 // gold per 1000 ticks left on the countdown
+}
+
+impl Default for WaveSettings {
+    fn default() -> Self {
+        Self {
+            build_ticks: 10000,
+            auto_start: true,
+            early_bonus: 1
+        }
+    }
 }
 
 impl Widget for &App {
@@ -67,186 +231,403 @@ impl Widget for &App {
         where
             Self: Sized {
 
-                let instructions = Title::from(text::Line::from(vec![
-                    " exit:".bold(),
-                    " <q> ".bold(),
-                    " restart:".bold(),
-                    " <Enter> ".bold()
-                ]));
-
-                let block = Block::default()
-                    .borders(Borders::NONE)
-                    .title(Title::from(" tower defense ".bold())
-                        .alignment(Alignment::Center)
-                        .position(Position::Top))
-                    .title(instructions
-                        .alignment(Alignment::Center)
-                        .position(Position::Bottom))
-                    .bg(Color::Black);
-
-                Paragraph::new(text::Line::from(vec!["score: ".bold(), self.score.to_string().into(), " | Gold: ".bold(), self.gold.to_string().into(), " | wave: ".bold(), self.round.to_string().into(), " | hitpoints: ".bold(), self.hitpoints.to_string().into()]))
+                let block = self.block();
+                let screen = self.screen(block.inner(area));
+                block.render(area, buf);
+                self.hit_map.clear();
+
+                let mut status = vec!["score: ".bold(), self.score.to_string().into()];
+                if self.freeplay {
+                    status.extend(vec![" | freeplay: ".bold(), self.freeplay_score.to_string().into()]);
+                }
+                status.extend(vec![" | Gold: ".bold(), self.gold_label().into(), " | wave: ".bold(), self.wave_label().into(), " | hitpoints: ".bold(), self.hitpoints_label().into(), " | speed: ".bold(), self.speed_label().into()]);
+                if let Some(left) = self.build_ticks_left {
+                    if left > 0 || self.wave_settings.auto_start {
+                        status.extend(vec![" | next wave in: ".bold(), left.div_ceil(1000).to_string().into()]);
+                    }
+                    let button = vec![format!(" {} start now", self.key(&[KeyCommand::StartWave])).bold().fg(self.theme.highlight), format!(" +{} $", self.early_bonus(left)).fg(self.theme.highlight)];
+                    let x = screen.status.x + text::Line::from(status.clone()).width() as u16;
+                    self.hit_map.register(Rect::new(x, screen.status.y, text::Line::from(button.clone()).width() as u16, 1).intersection(screen.status), Target::StartWave);
+                    status.extend(button);
+                    if !self.wave_settings.auto_start {
+                        status.push(format!(" (auto start off {})", self.key(&[KeyCommand::AutoStart])).into());
+                    }
+                }
+                let status = text::Line::from(status);
+                let info = text::Line::from(vec![if self.playback.is_some() { "replay | ".bold().fg(self.theme.highlight) } else { "".into() }, "mode: ".bold(), self.mode.name().into(), " | difficulty: ".bold(), self.difficulty.name().into(), " | highscore: ".bold(), self.highscore.to_string().into()]);
+                let fits = status.width() + info.width() < screen.status.width as usize;
+                Paragraph::new(status)
                     .alignment(Alignment::Left)
-                    .block(block.clone())
-                    .render(area, buf);
-
-                Paragraph::new(text::Line::from(vec!["highscore: ".bold(), self.highscore.to_string().into()]))
-                    .alignment(Alignment::Right)
-                    .block(block.clone())
-                    .render(area, buf);
-            
-
-                if !self.dead {
-                    Canvas::default()
-                        .block(block.clone())
-                        .x_bounds([-90.0, 90.0])
-                        .y_bounds([-90.0, 90.0])
-                        .background_color(Color::Black)
-                        .paint(|ctx| {
-                            for rect in self.path.elements.iter() { // draw the path of the balloons
-                                ctx.draw(&Rectangle {
-                                    x: rect.x,
-                                    y: rect.y,
-                                    height: rect.height,
-                                    width: rect.width,
-                                    color: Color::White
-                                })
-                            }
-                            ctx.layer();
-                            for ballon in self.balloons.iter() { // draw the balloons
-                                ballon.render_self(ctx);
-                            }
-                            ctx.layer();
-                            for tower in self.towers.iter() { // draw all projectiles
-                                for projectile in tower.projectiles.iter() {
-                                    if projectile.flying_time == 0 {
-                                        continue;
-                                    }
-                                    ctx.draw(&Circle {
-                                        x: projectile.x,
-                                        y: projectile.y,
-                                        radius: projectile.radius,
-                                        color: projectile.color
-                                    })
+                    .render(screen.status, buf);
+                if fits { // on narrow terminals the game status is more important
+                    Paragraph::new(info)
+                        .alignment(Alignment::Right)
+                        .render(screen.status, buf);
+                }
+
+                if self.dead || self.won {
+                    Paragraph::new(self.game_over_lines())
+                        .centered()
+                        .render(screen.body, buf);
+                    return;
+                }
+
+                self.hit_map.register(Block::bordered().inner(screen.playfield), Target::Playfield);
+                Canvas::default()
+                    .block(Block::bordered())
+                    .x_bounds([-90.0, 90.0])
+                    .y_bounds([-90.0, 90.0])
+                    .background_color(self.theme.background)
+                    .paint(|ctx| {
+                        for rect in self.path.elements.iter() { // draw the path of the balloons
+                            ctx.draw(&Rectangle {
+                                x: rect.x,
+                                y: rect.y,
+                                height: rect.height,
+                                width: rect.width,
+                                color: self.theme.path
+                            })
+                        }
+                        ctx.layer();
+                        for ballon in self.balloons.iter() { // draw the balloons
+                            ballon.render_self(ctx, &self.theme, self.show_health_bars);
+                        }
+                        ctx.layer();
+                        for tower in self.towers.iter() { // draw all projectiles
+                            for projectile in tower.projectiles.iter() {
+                                if projectile.flying_time == 0 {
+                                    continue;
                                 }
-                            }
-                            ctx.layer();
-                            for tower in self.towers.iter() { // draw the towers
-                                tower.render_self(ctx);
-                            }
-                            ctx.layer();
-                            if self.tower_shop_open {
-                                self.tower_shop.render_self(ctx);
-                                ctx.draw(&Rectangle {
-                                    x: -90.0,
-                                    y: -70.0,
-                                    width: 5.0,
-                                    height: 5.0,
-                                    color: Color::White
-                                });
-                                ctx.draw(&canvas::Line {
-                                    x1: -89.0,
-                                    y1: -66.0,
-                                    x2: -87.5,
-                                    y2: -69.0,
-                                    color: Color::White
-                                });
-                                ctx.draw(&canvas::Line {
-                                    x1: -86.0,
-                                    y1: -66.0,
-                                    x2: -87.5,
-                                    y2: -69.0,
-                                    color: Color::White
+                                ctx.draw(&Circle {
+                                    x: projectile.x,
+                                    y: projectile.y,
+                                    radius: projectile.radius,
+                                    color: self.theme.paint(projectile.paint)
                                 })
                             }
-                            else {
-                                ctx.draw(&Rectangle {
-                                    x: -90.0,
-                                    y: -90.0,
-                                    width: 5.0,
-                                    height: 5.0,
-                                    color: Color::White
-                                });
-                                ctx.draw(&canvas::Line {
-                                    x1: -89.0,
-                                    y1: -89.0,
-                                    x2: -87.5,
-                                    y2: -86.0,
-                                    color: Color::White
-                                });
-                                ctx.draw(&canvas::Line {
-                                    x1: -86.0,
-                                    y1: -89.0,
-                                    x2: -87.5,
-                                    y2: -86.0,
-                                    color: Color::White
-                                })
-                            }
-                            ctx.layer();
-                            for tower in self.towers.iter() {
-                                if self.tower_shop_open {
-                                    tower.upgrades.render_self(ctx, -20.0);
-                                }
-                                else {
-                                    tower.upgrades.render_self(ctx, 0.0);
-                                }
-                            }
-                            ctx.layer();
-                            match &self.new_tower {
-                                None => (),
-                                Some(tower) => tower.render_self(ctx),
-                            }
-                        })
-                        .render(area, buf);
+                        }
+                        ctx.layer();
+                        for tower in self.towers.iter() { // draw the towers
+                            tower.render_self(ctx, &self.theme);
+                        }
+                        ctx.layer();
+                        self.indicators.render(ctx, &self.theme);
+                        if let Some(idx) = self.upgrade_shop_open {
+                            self.towers[idx].render_range(ctx, self.theme.range);
+                        }
+                        match &self.new_tower {
+                            None => (),
+                            Some(tower) => tower.render_ghost(ctx, &self.theme, self.placement_valid(tower)),
+                        }
+                        if self.show_cursor {
+                            let (x, y) = self.cursor;
+                            ctx.draw(&canvas::Line {
+                                x1: x - 2.0,
+                                y1: y,
+                                x2: x + 2.0,
+                                y2: y,
+                                color: self.theme.cursor
+                            });
+                            ctx.draw(&canvas::Line {
+                                x1: x,
+                                y1: y - 2.0,
+                                x2: x,
+                                y2: y + 2.0,
+                                color: self.theme.cursor
+                            });
+                        }
+                    })
+                    .render(screen.playfield, buf);
+                if let Some(text) = self.log.borrow().banner(self.ticks) {
+                    self.render_banner(text, screen.playfield, buf);
                 }
-                else {
-                    Paragraph::new(text::Line::from(" dead ".bold().red()))
-                        .centered()
-                        .block(block.clone())
-                        .render(area, buf);
+
+                if let Some(rect) = screen.shop {
+                    let block = Block::bordered().title(format!(" shop {} ", self.key(&[KeyCommand::Shop])));
+                    self.hit_map.register_rows(block.inner(rect), (0..self.tower_shop.n_towers).map(|i| Some(Target::ShopItem(i))));
+                    Paragraph::new(self.shop_lines())
+                        .block(block)
+                        .render(rect, buf);
+                }
+                if let Some(rect) = screen.spawn_panel {
+                    let block = Block::bordered().title(format!(" spawn {} ", self.key(&[KeyCommand::SpawnPanel])));
+                    let targets = BalloonKind::ALL.iter().map(|kind| Some(Target::SpawnBalloon(*kind))).chain(std::iter::once(Some(Target::SpawnWave)));
+                    self.hit_map.register_rows(block.inner(rect), targets);
+                    Paragraph::new(self.spawn_panel_lines())
+                        .block(block)
+                        .render(rect, buf);
+                }
+                if let (Some(rect), Some(idx)) = (screen.tower_panel, self.upgrade_shop_open) {
+                    let tower = &self.towers[idx];
+                    let block = Block::bordered().title(format!(" {} {} ", tower.name, self.key(&[KeyCommand::Upgrades])));
+                    let (lines, rows): (Vec<_>, Vec<_>) = tower.panel(self.sell_refund, &self.theme, &self.key(&[KeyCommand::Sell])).into_iter().unzip();
+                    self.hit_map.register_rows(block.inner(rect), rows.into_iter().map(|row| row.map(|row| match row {
+                        PanelRow::Path(path) => Target::UpgradePath(path),
+                        PanelRow::Sell => Target::SellTower
+                    })));
+                    Paragraph::new(lines)
+                        .block(block)
+                        .render(rect, buf);
+                }
+
+                if let Some(rect) = screen.log {
+                    let block = Block::bordered().title(format!(" log {} ", self.key(&[KeyCommand::Log])));
+                    Paragraph::new(self.log.borrow().lines(block.inner(rect).height as usize, &self.theme))
+                        .block(block)
+                        .render(rect, buf);
                 }
-    }   
+
+                if self.on_pause {
+                    self.render_pause_menu(screen.body, buf);
+                }
+                if let Some(command) = self.confirming {
+                    self.render_confirmation(command, screen.body, buf);
+                }
+    }
 }
 
 impl App {
 
-    pub fn run(&mut self, terminal: &mut tui::Tui) -> Result<bool> {
+    pub fn run(&mut self, terminal: &mut tui::Tui) -> Result<Outcome> {
         let time = Duration::from_micros(100);
-        let mut wave = self.next_wave();
-        let mut wave_complete = false;
         loop {
-            if self.balloons.len() == 0 && wave_complete {
-                wave = self.next_wave();
-            }
             terminal.draw(|frame| self.render_frame(frame))?;
             if event::poll(time)? {
                 self.handle_events().wrap_err("handle events failed")?;
             }
             if self.exit {
-                break;
+                return Ok(Outcome::Quit);
             }
             if self.restart {
-                return Ok(false);
+                return Ok(Outcome::Restart);
             }
-            if self.on_pause || self.dead {
+            if self.to_menu {
+                return Ok(Outcome::Menu);
+            }
+            if self.dead || self.won || self.confirming.is_some() {
                 continue;
             }
-            wave_complete = self.handle_wave(&mut wave);
-            self.move_wave()?;
-            self.is_dead()?;
-            self.generate_projectiles()?;
-            self.handle_ballon_projectile_intereaction()?;
-            self.highscore();
+            if self.on_pause {
+                if self.step_requested {
+                    self.step_requested = false;
+                    self.tick()?;
+                }
+                continue;
+            }
+            for _ in 0..self.speed {
+                self.tick()?;
+                if self.dead || self.won {
+                    break;
+                }
+            }
+        }
+    }
+
+    pub fn tick(&mut self) -> Result<()> {
+        self.apply_playback();
+        if self.balloons.is_empty() && self.wave_complete {
+            self.wave_complete = false;
+            self.build_ticks_left = Some(self.wave_settings.build_ticks);
+            self.events.emit(self.ticks, GameEvent::WaveCleared { wave: self.round });
+            if !self.freeplay && self.final_wave.is_some_and(|wave| self.round >= wave) {
+                self.won = true;
+                self.victory_reached = true;
+                self.events.emit(self.ticks, GameEvent::Victory { wave: self.round });
+            }
+        }
+        match self.build_ticks_left {
+            Some(0) => {
+                if self.wave_settings.auto_start {
+                    self.start_wave();
+                }
+            },
+            Some(left) => self.build_ticks_left = Some(left - 1),
+            None => self.wave_complete = self.handle_wave()
+        }
+        self.move_wave()?;
+        self.is_dead()?;
+        self.generate_projectiles()?;
+        self.handle_ballon_projectile_intereaction()?;
+        self.highscore();
+        self.indicators.tick();
+        self.ticks += 1;
+        Ok(())
+    }
+
+    // runs the simulation as fast as possible until the player died or max_waves waves were survived, a victory goes on in freeplay
+    pub fn run_headless(&mut self, max_waves: usize) -> Result<()> {
+        while !self.dead && !self.stalled() {
+            if self.won {
+                self.act(Action::Freeplay);
+            }
+            if self.round >= max_waves && self.build_ticks_left.is_some() && self.round > 0 {
+                break;
+            }
+            self.tick()?;
+        }
+        Ok(())
+    }
+
+    // nothing will ever happen again without input, a sandbox game waiting for its next wave for example
+    fn stalled(&self) -> bool {
+        self.build_ticks_left == Some(0)
+            && !self.wave_settings.auto_start
+            && self.playback.as_ref().is_none_or(|actions| actions.is_empty())
+    }
+
+    // dead or waiting on the victory screen
+    pub fn is_over(&self) -> bool {
+        self.dead || self.won
+    }
+
+    pub fn won(&self) -> bool {
+        self.won
+    }
+
+    pub fn victory_reached(&self) -> bool {
+        self.victory_reached
+    }
+
+    // nothing is left to play for that counts, a game in freeplay was already won
+    pub fn finished(&self) -> bool {
+        self.dead || self.victory_reached
+    }
+
+    pub fn freeplay(&self) -> bool {
+        self.freeplay
+    }
+
+    pub fn building(&self) -> bool {
+        self.build_ticks_left.is_some()
+    }
+
+    // also restarts a running build countdown with the new length
+    pub fn set_wave_settings(&mut self, settings: WaveSettings) {
+        self.wave_settings = settings;
+        if self.build_ticks_left.is_some() {
+            self.build_ticks_left = Some(settings.build_ticks);
+        }
+    }
+
+    pub fn towers(&self) -> &[Tower] {
+        &self.towers
+    }
+
+    pub fn placement_valid(&self, tower: &Tower) -> bool {
+        !self.tower_on_path(tower) && !self.tower_collision(tower)
+    }
+
+    // the message log is shared with whoever else wants to announce something in it
+    pub fn log(&self) -> Rc<RefCell<MessageLog>> {
+        self.log.clone()
+    }
+
+    pub fn telemetry(&self) -> Ref<'_, Telemetry> {
+        self.telemetry.borrow()
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn balloons_left(&self) -> usize {
+        self.balloons.len()
+    }
+
+    pub fn leaked(&self) -> u64 {
+        self.leaked
+    }
+
+    pub fn gold(&self) -> u16 {
+        self.gold
+    }
+
+    pub fn hitpoints(&self) -> u16 {
+        self.hitpoints
+    }
+
+    pub fn observe(&self) -> Observation {
+        Observation {
+            tick: self.ticks,
+            wave: self.round,
+            gold: self.gold,
+            hitpoints: self.hitpoints,
+            score: self.score,
+            dead: self.dead,
+            won: self.won,
+            final_wave: self.final_wave,
+            build_ticks_left: self.build_ticks_left,
+            balloons: self.balloons.iter().map(|balloon| BalloonState {
+                x: balloon.x,
+                y: balloon.y,
+                radius: balloon.radius,
+                hitpoints: balloon.hitpoints(),
+                progress: balloon.total_x
+            }).collect(),
+            towers: self.towers.iter().map(|tower| TowerState {
+                kind: tower.kind,
+                area: Area::new(tower.x, tower.y, tower.width, tower.height),
+                range: tower.range(),
+                sell_value: tower.sell_value(self.sell_refund),
+                upgrades: tower.upgrades.paths.iter().enumerate().map(|(i, path)| PathState {
+                    name: path.name,
+                    level: path.level,
+                    next_cost: tower.upgrades.next_tier(i).map(|tier| tier.cost)
+                }).collect()
+            }).collect(),
+            shop: self.tower_shop.towers().iter().map(|tower| ShopEntry {
+                kind: tower.kind,
+                cost: tower.cost,
+                range: tower.range(),
+                width: tower.width,
+                height: tower.height
+            }).collect(),
+            path: self.path.elements.iter().map(|element| {
+                Area::new(element.x, element.y, element.width, element.height)
+            }).collect(),
+            field: Area::new(-90.0, -90.0, 180.0, 180.0)
         }
-        Ok(true)
     }
 
     fn render_frame(&self, frame: &mut Frame) {
         frame.render_widget(self, frame.size());
     }
 
+    fn gold_label(&self) -> String {
+        match self.mode {
+            GameMode::Sandbox => "∞".to_string(),
+            GameMode::Normal => self.gold.to_string()
+        }
+    }
+
+    fn wave_label(&self) -> String {
+        match self.final_wave {
+            Some(wave) if !self.freeplay => format!("{}/{}", self.round, wave),
+            _ => self.round.to_string()
+        }
+    }
+
+    fn hitpoints_label(&self) -> String {
+        match self.mode {
+            GameMode::Sandbox => "∞".to_string(),
+            GameMode::Normal => self.hitpoints.to_string()
+        }
+    }
+
+    fn speed_label(&self) -> String {
+        if self.on_pause {
+            return "paused".to_string();
+        }
+        format!("{}x", self.speed)
+    }
+
     fn highscore(&mut self) {
-        if self.score > self.highscore {
+        if self.ranked && self.score > self.highscore {
+            if !self.new_highscore && self.highscore > 0 { // beating nothing isn't worth a message
+                self.events.emit(self.ticks, GameEvent::NewHighscore { score: self.score });
+            }
             self.highscore = self.score;
+            self.new_highscore = true;
         }
     }
 
@@ -262,107 +643,266 @@ impl App {
                     format!("handling mouse event failed: \n{mouse_event:#?}")
                 })
             },
-            Event::Resize(cols, rows) => self.handle_resize_event(cols, rows).wrap_err_with(|| {
-                format!("handling mouse event failed: \n Resize event")
-            }),
+            Event::Resize(..) => Ok(()), // the next frame is laid out for the new size
            _ => Ok(())
         }
     }
 
-    pub fn new() -> Result<Self> {
-        let (cols, rows) = size()?;
+    pub fn new(settings: &GameSettings) -> Result<Self> {
+        let (difficulty, mode) = (settings.difficulty, settings.mode);
         let mut app = App {
             score: 0,
             highscore: 0,
             exit: false,
+            to_menu: false,
             dead: false,
+            won: false,
+            victory_reached: false,
+            final_wave: settings.map.final_wave.filter(|_| mode == GameMode::Normal),
+            freeplay: false,
+            freeplay_score: 0,
             on_pause: false,
-            path: BalloonPath::default(),
+            pause_selected: 0,
+            notice: None,
+            save_path: None,
+            new_highscore: false,
+            ranked: mode == GameMode::Normal && settings.start_wave == 1,
+            path: BalloonPath::from_map(&settings.map),
             balloons: vec![],
             towers: vec![],
-            balloon_factory: BalloonFactory::default(),
-            round: 0,
-            max_cols: cols,
-            max_rows: rows,
-            gold: 30,
-            hitpoints: 100,
+            balloon_factory: BalloonFactory::new(difficulty, settings.seed, settings.waves.clone()),
+            round: settings.start_wave.saturating_sub(1),
+            gold: difficulty.starting_gold(),
+            hitpoints: difficulty.hitpoints(),
             new_tower: None,
-            tower_shop: TowerShop::new(),
+            tower_shop: TowerShop::new(difficulty.cost_multiplier()),
             upgrade_shop_open: None,
-            tower_shop_open: false,
-            restart: false
+            tower_shop_open: true,
+            restart: false,
+            sell_refund: settings.sell_refund.clamp(0.0, 1.0),
+            sold_towers: vec![],
+            cursor: (0.0, 0.0),
+            show_cursor: false,
+            wave: BalloonWave::default(),
+            wave_complete: false,
+            speed: 1,
+            step_requested: false,
+            wave_settings: settings.wave_settings,
+            build_ticks_left: None,
+            difficulty,
+            mode,
+            spawn_panel_open: false,
+            seed: settings.seed,
+            map: settings.map.name.clone(),
+            ticks: 0,
+            replay: Replay {
+                settings: settings.clone(),
+                actions: vec![]
+            },
+            playback: None,
+            leaked: 0,
+            telemetry: Rc::new(RefCell::new(Telemetry::new(settings.start_wave))),
+            indicators: Indicators::default(),
+            show_health_bars: true,
+            show_damage: false,
+            theme: Theme::default(),
+            keymap: Keymap::default(),
+            confirming: None,
+            events: EventBus::default(),
+            log: Rc::default(),
+            log_open: true,
+            hit_map: HitMap::default()
         };
-        app.path.generate_path();
+        if mode == GameMode::Sandbox {
+            app.gold = u16::MAX;
+            app.wave_settings.auto_start = false;
+            app.wave_settings.build_ticks = 0;
+        }
+        app.build_ticks_left = Some(app.wave_settings.build_ticks);
+        app.events.subscribe(Box::new(app.telemetry.clone()));
+        app.events.subscribe(Box::new(app.log.clone()));
         Ok(app)
     }
 
+    fn pick(&mut self, index: usize) {
+        if self.spawn_panel_open {
+            if let Some(kind) = BalloonKind::ALL.get(index) {
+                self.player_act(Action::SpawnBalloon { kind: *kind });
+            }
+        }
+        else if let Some(idx) = self.upgrade_shop_open { // while the upgrade panel is open the number keys buy upgrades
+            self.player_act(Action::Upgrade { tower: idx, path: index });
+        }
+        else {
+            self.show_cursor = true;
+            self.new_tower = self.tower_shop.tower(index, &self.gold);
+            if let Some(tower) = self.new_tower.as_mut() {
+                (tower.x, tower.y) = self.cursor;
+            }
+        }
+    }
+
     fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
-        match key_event.code {
-            KeyCode::Char('q') => self.exit(),
-            KeyCode::Esc => self.pause()?,
-            KeyCode::Enter => self.restart(),
-            KeyCode::Right => {},
+        let code = key_event.code;
+        if let Some(command) = self.confirming.take() { // every other key says no
+            if self.keymap.command(code, &[KeyCommand::Confirm]).is_some() {
+                self.execute(command);
+            }
+            return Ok(());
+        }
+        if self.dead || self.won {
+            match self.keymap.command(code, &GAME_OVER_KEYS) {
+                Some(KeyCommand::Quit) => self.exit(),
+                Some(KeyCommand::Restart) => self.restart(),
+                Some(KeyCommand::Menu) => self.to_menu = true,
+                Some(KeyCommand::Freeplay) if self.won => {
+                    self.player_act(Action::Freeplay);
+                },
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.on_pause {
+            match self.keymap.command(code, &PAUSE_KEYS) {
+                Some(KeyCommand::Pause) => self.pause()?,
+                Some(KeyCommand::Quit) => self.confirming = Some(KeyCommand::Quit),
+                Some(KeyCommand::Up) => self.pause_selected = self.pause_selected.saturating_sub(1),
+                Some(KeyCommand::Down) => self.pause_selected = (self.pause_selected + 1).min(PauseItem::ALL.len() - 1),
+                Some(KeyCommand::Select) => self.choose(PauseItem::ALL[self.pause_selected]),
+                Some(KeyCommand::Step) => self.step_requested = true,
+                _ => {}
+            }
+            return Ok(());
+        }
+        let Some(command) = self.keymap.command(code, &GAME_KEYS) else { // digits that aren't bound to anything pick from the open panel
+            if let KeyCode::Char(c @ '1'..='9') = code {
+                self.pick(c as usize - '1' as usize);
+            }
+            return Ok(());
+        };
+        match command {
+            KeyCommand::Quit | KeyCommand::Restart => self.confirming = Some(command),
+            KeyCommand::Pause => self.pause()?,
+            KeyCommand::Sell => {
+                if let Some(idx) = self.upgrade_shop_open {
+                    self.player_act(Action::Sell { tower: idx });
+                }
+            },
+            KeyCommand::Left => self.move_cursor(-CURSOR_STEP, 0.0),
+            KeyCommand::Right => self.move_cursor(CURSOR_STEP, 0.0),
+            KeyCommand::Up => self.move_cursor(0.0, CURSOR_STEP),
+            KeyCommand::Down => self.move_cursor(0.0, -CURSOR_STEP),
+            KeyCommand::FastLeft => self.move_cursor(-CURSOR_STEP * 4.0, 0.0),
+            KeyCommand::FastRight => self.move_cursor(CURSOR_STEP * 4.0, 0.0),
+            KeyCommand::FastUp => self.move_cursor(0.0, CURSOR_STEP * 4.0),
+            KeyCommand::FastDown => self.move_cursor(0.0, -CURSOR_STEP * 4.0),
+            KeyCommand::SpawnPanel if self.mode == GameMode::Sandbox => self.spawn_panel_open = !self.spawn_panel_open,
+            KeyCommand::SpawnWave if self.spawn_panel_open => {
+                self.player_act(Action::SpawnWave);
+            },
+            KeyCommand::Select => {
+                if self.new_tower.is_some() {
+                    self.place_new_tower();
+                }
+                else if let Some(idx) = self.mouse_over_tower(self.cursor.0, self.cursor.1) {
+                    self.toggle_upgrade_panel(idx);
+                }
+            },
+            KeyCommand::Upgrades => {
+                if let Some(idx) = self.upgrade_shop_open {
+                    self.toggle_upgrade_panel(idx);
+                }
+                else if let Some(idx) = self.mouse_over_tower(self.cursor.0, self.cursor.1) {
+                    self.toggle_upgrade_panel(idx);
+                }
+            },
+            KeyCommand::Cancel => self.new_tower = None,
+            KeyCommand::Shop => self.tower_shop_open = !self.tower_shop_open,
+            KeyCommand::Log => self.log_open = !self.log_open,
+            KeyCommand::SpeedUp => self.speed = (self.speed + 1).min(MAX_SPEED),
+            KeyCommand::SpeedDown => self.speed = (self.speed - 1).max(1),
+            KeyCommand::StartWave => {
+                self.player_act(Action::StartWave);
+            },
+            KeyCommand::AutoStart => {
+                self.player_act(Action::SetAutoStart { enabled: !self.wave_settings.auto_start });
+            },
             _ => {}
         }
         Ok(())
     }
 
     fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> Result<()> {
-        let y = self.row_to_y(mouse_event.row);
-        let x = self.col_to_x(mouse_event.column);
+        let (col, row) = (mouse_event.column, mouse_event.row);
+        let hit = self.hit_map.hit(col, row);
+        if let Some(command) = self.confirming {
+            if let (MouseEventKind::Down(MouseButton::Left), Some((_, Target::Confirm(yes)))) = (mouse_event.kind, hit) {
+                self.confirming = None;
+                if yes {
+                    self.execute(command);
+                }
+            }
+            return Ok(());
+        }
+        if self.dead || self.won {
+            return Ok(());
+        }
+        if self.on_pause {
+            if let (MouseEventKind::Down(MouseButton::Left), Some((_, Target::MenuItem(index)))) = (mouse_event.kind, hit) {
+                self.pause_selected = index;
+                self.choose(PauseItem::ALL[index]);
+            }
+            return Ok(());
+        }
         match mouse_event.kind {
             MouseEventKind::Drag(MouseButton::Left) => {
-                if let Some(tower) = self.new_tower.as_mut() {
-                    tower.x = x;
-                    tower.y = y;
+                if let (Some(tower), Some((area, Target::Playfield))) = (self.new_tower.as_mut(), hit) {
+                    (tower.x, tower.y) = world_point(area, col, row);
                 }
             }
             MouseEventKind::Up(MouseButton::Left) => {
-                if y > -70.0 || !self.tower_shop_open {
-                    if let Some(tower) = self.new_tower.as_ref() {
-                        if !self.tower_on_path(tower) && !self.tower_collision(tower) {
-                            self.towers.push(tower.clone());
-                            self.gold -= self.towers[self.towers.len() - 1].cost;
-                            self.new_tower = None;
-                        }
-                    }
-                }
-                else {
-                    self.new_tower = None;
+                if let Some((_, Target::Playfield)) = hit {
+                    self.place_new_tower();
                 }
             }
             MouseEventKind::Down(MouseButton::Left) => {
-                if y <= -70.0 {
-                    if self.tower_shop_open {
-                        self.new_tower = self.tower_shop.get_tower(x, &self.gold);
-                    }
-                    else {
-                        if y <= -83.0 && x <= -85.0 {
-                            self.tower_shop_open = true;
+                self.show_cursor = false;
+                let Some((area, target)) = hit else {
+                    return Ok(());
+                };
+                match target {
+                    Target::ShopItem(index) => self.new_tower = self.tower_shop.tower(index, &self.gold),
+                    Target::SpawnBalloon(kind) => {
+                        self.player_act(Action::SpawnBalloon { kind });
+                    },
+                    Target::SpawnWave => {
+                        self.player_act(Action::SpawnWave);
+                    },
+                    Target::UpgradePath(path) => {
+                        if let Some(idx) = self.upgrade_shop_open {
+                            self.player_act(Action::Upgrade { tower: idx, path });
                         }
-                    }
-                }
-                else if y <= -65.0 && y >= -70.0 && x <= -85.0 && self.tower_shop_open{
-                    self.tower_shop_open = false;
-                }
-                else if let Some(idx) = self.upgrade_shop_open {
-                    if x >= 70.0 && (y >= -70.0 || !self.tower_shop_open) {
-                        if let Some(cost) = self.towers[idx].buy_upgrade(y, &self.gold) {
-                            self.gold -= cost;
+                    },
+                    Target::SellTower => {
+                        if let Some(idx) = self.upgrade_shop_open {
+                            self.player_act(Action::Sell { tower: idx });
+                        }
+                    },
+                    Target::StartWave => {
+                        self.player_act(Action::StartWave);
+                    },
+                    Target::MenuItem(_) | Target::Confirm(_) => {}
+                    Target::Playfield => {
+                        let (x, y) = world_point(area, col, row);
+                        if let Some(tower) = self.new_tower.as_mut() { // placed when the button is released
+                            (tower.x, tower.y) = (x, y);
+                        }
+                        else if let Some(idx) = self.mouse_over_tower(x, y) {
+                            self.toggle_upgrade_panel(idx);
+                        }
+                        else if let Some(idx) = self.upgrade_shop_open {
+                            self.toggle_upgrade_panel(idx);
                         }
-                    }
-                    else {
-                        self.towers[idx].show_upgrades();
-                        self.upgrade_shop_open = None;
-                    }
-                }
-                else if let Some(idx) = self.mouse_over_tower(x, y) {
-                    self.towers[idx].show_upgrades();
-                    if self.towers[idx].upgrades.show_upgrades {
-                        self.upgrade_shop_open = Some(idx)
-                    }
-                    else {
-                        self.upgrade_shop_open = None;
                     }
                 }
             }
@@ -371,9 +911,144 @@ impl App {
         Ok(())
     }
 
-    fn handle_resize_event(&mut self, cols: u16, rows: u16) -> Result<()> {
-        (self.max_cols, self.max_rows) = (cols, rows);
-        Ok(())
+    fn move_cursor(&mut self, dx: f64, dy: f64) {
+        self.show_cursor = true;
+        self.cursor.0 = (self.cursor.0 + dx).clamp(-90.0, 90.0);
+        self.cursor.1 = (self.cursor.1 + dy).clamp(-90.0, 90.0);
+        if let Some(tower) = self.new_tower.as_mut() {
+            (tower.x, tower.y) = self.cursor;
+        }
+    }
+
+    fn place_new_tower(&mut self) {
+        if let Some(tower) = self.new_tower.as_ref() {
+            let action = Action::PlaceTower { kind: tower.kind, x: tower.x, y: tower.y };
+            if self.player_act(action) {
+                self.new_tower = None;
+            }
+        }
+    }
+
+    // input from the keyboard or mouse, ignored while a replay is playing
+    fn player_act(&mut self, action: Action) -> bool {
+        if self.playback.is_some() {
+            return false;
+        }
+        self.act(action)
+    }
+
+    // applies the action and records it for the replay if it went through
+    pub fn act(&mut self, action: Action) -> bool {
+        let done = self.perform(action);
+        if done {
+            self.replay.actions.push((self.ticks, action));
+        }
+        done
+    }
+
+    fn perform(&mut self, action: Action) -> bool {
+        match action {
+            Action::PlaceTower { kind, x, y } => {
+                let Some(mut tower) = self.tower_shop.tower_of_kind(kind, &self.gold) else {
+                    return false;
+                };
+                (tower.x, tower.y) = (x, y);
+                if !self.placement_valid(&tower) {
+                    return false;
+                }
+                self.spend(tower.cost);
+                self.events.emit(self.ticks, GameEvent::TowerBuilt { kind, cost: tower.cost });
+                self.towers.push(tower);
+                true
+            },
+            Action::Upgrade { tower, path } => {
+                if tower >= self.towers.len() {
+                    return false;
+                }
+                match self.towers[tower].buy_upgrade(path, &self.gold) {
+                    Some(tier) => {
+                        self.spend(tier.cost);
+                        self.events.emit(self.ticks, GameEvent::TowerUpgraded { kind: self.towers[tower].kind, upgrade: tier.name, cost: tier.cost });
+                        true
+                    },
+                    None => false
+                }
+            },
+            Action::Sell { tower } => {
+                if tower >= self.towers.len() {
+                    return false;
+                }
+                self.sell_tower(tower);
+                true
+            },
+            Action::StartWave => {
+                let building = self.build_ticks_left.is_some();
+                self.start_wave();
+                building
+            },
+            Action::SetAutoStart { enabled } => {
+                self.wave_settings.auto_start = enabled;
+                true
+            },
+            Action::SpawnBalloon { kind } => {
+                if self.mode != GameMode::Sandbox {
+                    return false;
+                }
+                self.spawn_balloon(kind);
+                true
+            },
+            Action::SpawnWave => {
+                if self.mode != GameMode::Sandbox {
+                    return false;
+                }
+                self.spawn_wave();
+                true
+            },
+            Action::Freeplay => {
+                if !self.won {
+                    return false;
+                }
+                self.won = false;
+                self.freeplay = true;
+                self.events.emit(self.ticks, GameEvent::FreeplayStarted);
+                true
+            }
+        }
+    }
+
+    pub fn play_back(&mut self, replay: &Replay) {
+        self.playback = Some(replay.actions.iter().copied().collect());
+        self.ranked = false;
+    }
+
+    pub fn ranked(&self) -> bool {
+        self.ranked
+    }
+
+    fn apply_playback(&mut self) {
+        let ticks = self.ticks;
+        while let Some((tick, action)) = self.playback.as_mut().and_then(|actions| actions.front().copied()) {
+            if tick > ticks {
+                break;
+            }
+            self.playback.as_mut().unwrap().pop_front();
+            self.act(action);
+        }
+    }
+
+    fn toggle_upgrade_panel(&mut self, idx: usize) {
+        if let Some(open) = self.upgrade_shop_open {
+            if open != idx {
+                self.towers[open].show_upgrades();
+            }
+        }
+        self.towers[idx].show_upgrades();
+        if self.towers[idx].upgrades.show_upgrades {
+            self.upgrade_shop_open = Some(idx)
+        }
+        else {
+            self.upgrade_shop_open = None;
+        }
     }
 
     fn restart(&mut self) {
@@ -390,14 +1065,272 @@ impl App {
         }
         else {
             self.on_pause = true;
+            self.pause_selected = 0;
+            self.notice = None;
         }
         Ok(())
     }
 
+    fn choose(&mut self, item: PauseItem) {
+        match item {
+            PauseItem::Resume => self.on_pause = false,
+            PauseItem::Restart => self.confirming = Some(KeyCommand::Restart),
+            PauseItem::Save => self.notice = Some(match self.save() {
+                Ok(()) => "game saved".to_string(),
+                Err(error) => format!("saving failed: {error}")
+            }),
+            PauseItem::Quit => self.confirming = Some(KeyCommand::Menu)
+        }
+    }
+
+    // what a confirmed restart or quit does
+    fn execute(&mut self, command: KeyCommand) {
+        match command {
+            KeyCommand::Restart => self.restart(),
+            KeyCommand::Menu => self.to_menu = true,
+            _ => self.exit()
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = self.save_path.as_ref().filter(|_| self.playback.is_none()) else {
+            return Err(eyre!("this game can't be saved"));
+        };
+        SaveGame {
+            replay: self.replay.clone(),
+            ticks: self.ticks
+        }.save(path)?;
+        Ok(())
+    }
+
+    // plays the saved game back up to the moment it was saved, then hands it to the player
+    pub fn resume(save: &SaveGame) -> Result<Self> {
+        let mut app = App::new(&save.replay.settings)?;
+        let ranked = app.ranked;
+        app.play_back(&save.replay);
+        while app.ticks < save.ticks && !app.dead {
+            app.tick()?;
+        }
+        app.playback = None;
+        app.ranked = ranked; // the highscore is checked again on the next tick
+        Ok(app)
+    }
+
+    pub fn set_speed(&mut self, speed: u8) {
+        self.speed = speed.clamp(1, MAX_SPEED);
+    }
+
+    fn spend(&mut self, cost: u16) {
+        if self.mode != GameMode::Sandbox {
+            self.gold -= cost;
+        }
+    }
+
+    fn earn(&mut self, gold: u16) {
+        self.gold = self.gold.saturating_add(gold);
+    }
+
+    fn lose_hitpoints(&mut self, damage: u16) {
+        if self.mode != GameMode::Sandbox {
+            self.hitpoints = self.hitpoints.saturating_sub(damage);
+        }
+    }
+
+    // the title on top and the keys at the bottom, everything else is drawn inside of it
+    fn block(&self) -> Block<'static> {
+        let key = |commands: &[KeyCommand]| format!(" {} ", self.key(commands)).bold();
+        let mut keys = vec![
+            " exit:".bold(),
+            key(&[KeyCommand::Quit]),
+            " restart:".bold(),
+            key(&[KeyCommand::Restart]),
+            " move:".bold(),
+            key(&[KeyCommand::Left, KeyCommand::Down, KeyCommand::Up, KeyCommand::Right]),
+            " buy/upgrade:".bold(),
+            " <1-9> ".bold(),
+            " place:".bold(),
+            key(&[KeyCommand::Select]),
+            " upgrades:".bold(),
+            key(&[KeyCommand::Upgrades]),
+            " sell:".bold(),
+            key(&[KeyCommand::Sell]),
+            " shop:".bold(),
+            key(&[KeyCommand::Shop]),
+            " log:".bold(),
+            key(&[KeyCommand::Log]),
+            " pause:".bold(),
+            key(&[KeyCommand::Pause]),
+            " speed:".bold(),
+            key(&[KeyCommand::SpeedUp, KeyCommand::SpeedDown]),
+            " step:".bold(),
+            key(&[KeyCommand::Step])
+        ];
+        if self.mode == GameMode::Sandbox {
+            keys.extend(vec![" spawn:".bold(), key(&[KeyCommand::SpawnPanel])]);
+        }
+        let instructions = Title::from(text::Line::from(keys));
+
+        Block::default()
+            .borders(Borders::NONE)
+            .title(Title::from(" tower defense ".bold())
+                .alignment(Alignment::Center)
+                .position(Position::Top))
+            .title(instructions
+                .alignment(Alignment::Center)
+                .position(Position::Bottom))
+            .bg(self.theme.background)
+    }
+
+    fn screen(&self, area: Rect) -> Screen {
+        Screen::new(area, &Panels {
+            shop: self.tower_shop_open.then_some(self.tower_shop.n_towers as u16),
+            spawn_panel: self.spawn_panel_open.then_some(BalloonKind::ALL.len() as u16 + 1),
+            tower_panel: self.upgrade_shop_open.is_some(),
+            log: self.log_open.then_some(LOG_ROWS)
+        })
+    }
+
+    // "<q>" for the instruction bar and panel titles, from the first key bound to each command
+    fn key(&self, commands: &[KeyCommand]) -> String {
+        format!("<{}>", self.keymap.label(commands))
+    }
+
+    fn render_pause_menu(&self, area: Rect, buf: &mut Buffer) {
+        let popup = popup(area, 30, PauseItem::ALL.len() as u16 + 4);
+        let block = Block::bordered().title(format!(" paused {} ", self.key(&[KeyCommand::Pause])).bold());
+        let inner = block.inner(popup);
+        let mut lines: Vec<text::Line> = PauseItem::ALL.iter().enumerate().map(|(i, item)| {
+            if i == self.pause_selected {
+                text::Line::from(format!("> {}", item.label())).fg(self.theme.highlight).bold()
+            }
+            else {
+                text::Line::from(format!("  {}", item.label()))
+            }
+        }).collect();
+        lines.push(text::Line::from(""));
+        lines.push(text::Line::from(self.notice.clone().unwrap_or_else(|| format!("step {}", self.key(&[KeyCommand::Step])))).fg(self.theme.muted));
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .block(block)
+            .render(popup, buf);
+        self.hit_map.register_rows(inner, (0..PauseItem::ALL.len()).map(|i| Some(Target::MenuItem(i))));
+    }
+
+    // an announcement at the top of the playfield, out of the way of the popups in the middle
+    fn render_banner(&self, text: &str, area: Rect, buf: &mut Buffer) {
+        if area.height < 4 {
+            return;
+        }
+        let width = (text.chars().count() as u16 + 4).min(area.width);
+        let banner = Rect::new(area.x + (area.width - width) / 2, area.y + 1, width, 3);
+        Clear.render(banner, buf);
+        Paragraph::new(text::Line::from(text.to_string().bold().fg(self.theme.highlight)).centered())
+            .block(Block::bordered().border_style(self.theme.good))
+            .render(banner, buf);
+    }
+
+    fn render_confirmation(&self, command: KeyCommand, area: Rect, buf: &mut Buffer) {
+        let question = match command {
+            KeyCommand::Restart => " restart this game? ",
+            KeyCommand::Menu => " leave to the menu? ",
+            _ => " quit the game? "
+        };
+        let popup = popup(area, 30, 4);
+        let block = Block::bordered().title(question.bold()).border_style(self.theme.bad);
+        let inner = block.inner(popup);
+        Clear.render(popup, buf);
+        Paragraph::new(vec![
+            text::Line::from(format!("yes {}", self.key(&[KeyCommand::Confirm]))).fg(self.theme.highlight),
+            text::Line::from("no <any other key>")
+        ])
+            .block(block)
+            .render(popup, buf);
+        self.hit_map.register_rows(inner, [Some(Target::Confirm(true)), Some(Target::Confirm(false))]);
+    }
+
+    fn game_over_lines(&self) -> Vec<text::Line<'static>> {
+        let waves = self.telemetry.borrow().waves(self.ticks);
+        let total = |count: fn(&WaveTelemetry) -> u64| waves.iter().map(count).sum::<u64>();
+        let popped = total(|wave| wave.popped.values().sum());
+        let leaked = total(|wave| wave.leaked.values().sum());
+        let title = if self.won { " victory ".bold().fg(self.theme.good) } else { " game over ".bold().fg(self.theme.bad) };
+        let mut lines = vec![
+            text::Line::from(title),
+            text::Line::from("")
+        ];
+        if self.new_highscore {
+            lines.push(text::Line::from("new highscore!".bold().fg(self.theme.highlight).slow_blink()));
+            lines.push(text::Line::from(""));
+        }
+        if self.freeplay {
+            lines.push(text::Line::from(vec!["freeplay score: ".bold(), self.freeplay_score.to_string().into(), " (not counted for the highscore)".fg(self.theme.muted)]));
+        }
+        lines.extend(vec![
+            text::Line::from(vec!["score: ".bold(), self.score.to_string().into(), " | wave reached: ".bold(), self.round.to_string().into(), " | highscore: ".bold(), self.highscore.to_string().into()]),
+            text::Line::from(vec!["balloons popped: ".bold(), popped.to_string().into(), " | leaked: ".bold(), leaked.to_string().into(), " | time: ".bold(), format!("{}s", self.ticks / 1000).into()]),
+            text::Line::from(vec!["gold earned: ".bold(), total(|wave| wave.gold_earned).to_string().into(), " | spent: ".bold(), total(|wave| wave.gold_spent).to_string().into(), " | towers built: ".bold(), total(|wave| wave.towers_built).to_string().into(), " | upgrades: ".bold(), total(|wave| wave.towers_upgraded).to_string().into()]),
+            text::Line::from(""),
+            text::Line::from(format!("{:<16}{:>8}{:>10}{:>8}{:>8}{:>8}", "tower", "pops", "damage", "shots", "misses", "gold")).bold()
+        ]);
+        let towers = self.towers.iter().map(|tower| (tower.name, tower.stats, "")).chain(self.sold_towers.iter().map(|(name, stats)| (*name, *stats, " (sold)")));
+        for (name, stats, note) in towers {
+            lines.push(text::Line::from(format!("{:<16}{:>8}{:>10.0}{:>8}{:>8}{:>8}{}", name, stats.pops, stats.damage, stats.shots, stats.misses, stats.gold, note)));
+        }
+        lines.push(text::Line::from(""));
+        let key = |command: KeyCommand| format!(" {} ", self.key(&[command])).bold();
+        let mut keys = vec![" play again:".bold(), key(KeyCommand::Restart), " menu:".bold(), key(KeyCommand::Menu), " quit:".bold(), key(KeyCommand::Quit)];
+        if self.won {
+            keys.splice(0..0, vec![" freeplay:".bold(), key(KeyCommand::Freeplay)]);
+        }
+        lines.push(text::Line::from(keys));
+        lines
+    }
+
+    fn shop_lines(&self) -> Vec<text::Line<'static>> {
+        let held = self.new_tower.as_ref().map(|tower| tower.kind);
+        self.tower_shop.towers().iter().enumerate().map(|(i, tower)| {
+            let line = text::Line::from(format!("<{}> {:<16}{:>5} $", i + 1, tower.name, tower.cost));
+            if held == Some(tower.kind) {
+                line.fg(self.theme.highlight)
+            }
+            else if self.gold < tower.cost {
+                line.fg(self.theme.muted)
+            }
+            else {
+                line.fg(self.theme.text)
+            }
+        }).collect()
+    }
+
+    // one row per balloon kind plus one for a whole wave
+    fn spawn_panel_lines(&self) -> Vec<text::Line<'static>> {
+        BalloonKind::ALL.iter().enumerate()
+            .map(|(i, kind)| text::Line::from(format!("<{}> {}", i + 1, kind.name())))
+            .chain(std::iter::once(text::Line::from(format!("{} next wave", self.key(&[KeyCommand::SpawnWave])))))
+            .collect()
+    }
+
+    fn spawn_balloon(&mut self, kind: BalloonKind) {
+        let balloon = self.balloon_factory.balloon(kind, self.path.elements[0].x, self.path.elements[0].y);
+        self.events.emit(self.ticks, GameEvent::BalloonSpawned { kind });
+        self.balloons.push(balloon);
+    }
+
+    fn spawn_wave(&mut self) {
+        if self.build_ticks_left.is_some() {
+            self.start_wave();
+        }
+        else {
+            let wave = self.next_wave();
+            self.wave.append(wave);
+        }
+    }
+
     fn is_dead(&mut self) -> Result<()> {
         if !self.dead {
             if self.hitpoints <= 0 {
                 self.dead = true;
+                self.events.emit(self.ticks, GameEvent::GameOver { wave: self.round });
             }
         }
         Ok(())
@@ -405,19 +1338,38 @@ impl App {
 
     fn next_wave(&mut self) -> BalloonWave {
         self.round += 1;
+        self.events.emit(self.ticks, GameEvent::WaveStarted { wave: self.round });
         let wave = self.balloon_factory.generate_wave(self.round, self.path.elements[0].x, self.path.elements[0].y);
         wave
     }
 
-    fn handle_wave(&mut self, wave: &mut BalloonWave) -> bool {
-        if wave.ticks_since_last < wave.ticks_till_balloon {
-            let _ = wave.next();
+    // ends the build phase, calling a wave before the countdown ran out pays a bonus for the remaining time
+    fn start_wave(&mut self) {
+        if let Some(left) = self.build_ticks_left.take() {
+            let bonus = self.early_bonus(left);
+            if bonus > 0 {
+                self.earn(bonus);
+                self.events.emit(self.ticks, GameEvent::EarlyBonus { gold: bonus });
+            }
+            self.wave = self.next_wave();
+        }
+    }
+
+    fn early_bonus(&self, ticks_left: u32) -> u16 {
+        let bonus = (ticks_left / 1000) as u64 * self.wave_settings.early_bonus as u64;
+        bonus.min(u16::MAX as u64) as u16
+    }
+
+    fn handle_wave(&mut self) -> bool {
+        if self.wave.ticks_since_last < self.wave.ticks_till_balloon {
+            let _ = self.wave.next();
             return false;
         }
-        let next_ballon = wave.next();
+        let next_ballon = self.wave.next();
         match next_ballon {
             None => return true,
             Some(bloon) => {
+                self.events.emit(self.ticks, GameEvent::BalloonSpawned { kind: bloon.kind });
                 self.balloons.push(bloon);
             }
         }
@@ -428,7 +1380,10 @@ impl App {
         let mut k = 0;
         for i in 0..self.balloons.len() {
             if !self.balloons[i - k].move_balloon(&self.path)? {
-                self.hitpoints -= self.balloons[i - k].damage;
+                let hitpoints = self.hitpoints;
+                self.lose_hitpoints(self.balloons[i - k].damage);
+                self.events.emit(self.ticks, GameEvent::BalloonLeaked { kind: self.balloons[i - k].kind, lives: hitpoints - self.hitpoints });
+                self.leaked += 1;
                 self.balloons.remove(i - k);
                 k += 1;
             }
@@ -439,24 +1394,6 @@ impl App {
         Ok(())
     }
 
-    fn row_to_y(&self, row: u16) -> f64 {
-        let max = self.max_rows as f64;
-        let mut y = row as f64 - max + 1.0; // range: (1.0?)0.0..max -> 0.0..1.0 -> 0.0..180.0 -> -90.0..90.0
-        y /= -max;
-        y *= 180.0;
-        y -= 90.0;
-        y
-    }
-
-    fn col_to_x(&self, col: u16) -> f64 {
-        let max = self.max_cols as f64;
-        let mut x = col as f64 + 1.0; // range: (1.0?)0.0..max -> 0.0..1.0 -> 0.0..180.0 -> -90.0..90.0
-        x /= max;
-        x *= 180.0;
-        x -= 90.0;
-        x
-    }
-
     fn tower_on_path(&self, tower: &Tower) -> bool {
         self.path.point_on_path(tower)
     }
@@ -470,6 +1407,23 @@ impl App {
         None
     }
 
+    // removing the tower also drops all of its projectiles that are still in flight
+    fn sell_tower(&mut self, idx: usize) {
+        let tower = self.towers.remove(idx);
+        let refund = tower.sell_value(self.sell_refund);
+        self.earn(refund);
+        self.events.emit(self.ticks, GameEvent::TowerSold { kind: tower.kind, refund });
+        if let Some(open) = self.upgrade_shop_open {
+            if open == idx {
+                self.upgrade_shop_open = None;
+            }
+            else if open > idx {
+                self.upgrade_shop_open = Some(open - 1);
+            }
+        }
+        self.sold_towers.push((tower.name, tower.stats));
+    }
+
     fn tower_collision(&self, tower: &Tower) -> bool {
         self.towers.iter().any(|tower_| tower_.collides(tower)) || self.towers.iter().any(|tower_| tower.collides(tower_))
     }
@@ -484,8 +1438,14 @@ impl App {
             for i in 0..self.balloons.len() {
                 if self.balloons[i - k].is_dead() {
                     let (gold, score) = self.balloons[i - k].reward;
-                    self.gold += gold;
-                    self.score += score;
+                    self.gold = self.gold.saturating_add(gold);
+                    self.events.emit(self.ticks, GameEvent::BalloonPopped { kind: self.balloons[i - k].kind, gold, score });
+                    if self.freeplay {
+                        self.freeplay_score += score;
+                    }
+                    else {
+                        self.score += score;
+                    }
                     self.balloons.remove(i - k);
                     k += 1;
                     continue;
@@ -500,8 +1460,28 @@ impl App {
 
     fn handle_ballon_projectile_intereaction(&mut self) -> Result<()> {
         for i in 0..self.balloons.len() {
-            let dmg = self.damage_ballon(i)?;
-            self.balloons[i].reduce_hitpoints(dmg);
+            for tower in self.towers.iter_mut() {
+                let dmg = tower.calculate_damage(i);
+                if dmg == 0.0 || self.balloons[i].is_dead() {
+                    continue;
+                }
+                tower.stats.damage += dmg.min(self.balloons[i].hitpoints());
+                self.balloons[i].reduce_hitpoints(dmg);
+                tower.register_hits(i);
+                let balloon = &mut self.balloons[i];
+                if balloon.is_dead() { // the reward is paid out in generate_projectiles
+                    tower.stats.pops += 1;
+                    tower.stats.gold += balloon.reward.0 as u64;
+                }
+                if self.show_damage {
+                    if balloon.is_dead() {
+                        self.indicators.pop(balloon.x, balloon.y + balloon.radius);
+                    }
+                    else if let Some(damage) = balloon.damage_to_show(dmg, self.ticks) {
+                        self.indicators.damage(balloon.x, balloon.y + balloon.radius, damage);
+                    }
+                }
+            }
             if self.balloons[i].is_dead() {
                 for tower in self.towers.iter_mut() {
                     tower.remove_target_of_projectile(i)?;
@@ -512,13 +1492,6 @@ impl App {
         Ok(())
     }
 
-    fn damage_ballon(&mut self, index: usize) -> Result<f64> {
-        let mut dmg = 0.0;
-        for tower in self.towers.iter() {
-            dmg += tower.calculate_damage(index);
-        }
-        Ok(dmg)
-    }
 
 }
 
@@ -529,12 +1502,13 @@ pub struct BalloonPath {
 }
 
 impl BalloonPath {
-    fn generate_path(&mut self) {
-        self.elements.push(RectangleInPath::horizontal(-90.0, 0.0, 0.0));
-        self.elements.push(RectangleInPath::vertical(0.0, 40.0, 0.0));
-        self.elements.push(RectangleInPath::horizontal(-45.0, 0.0, 30.0));
-        self.elements.push(RectangleInPath::vertical(-10.0, 40.0, -45.0));
-        self.elements.push(RectangleInPath::horizontal(-45.0, 90.0, -10.0));
+    fn from_map(map: &Map) -> Self {
+        Self {
+            elements: map.segments.iter().map(|segment| match *segment {
+                Segment::Horizontal { x0, x1, y } => RectangleInPath::horizontal(x0, x1, y),
+                Segment::Vertical { y0, y1, x } => RectangleInPath::vertical(y0, y1, x)
+            }).collect()
+        }
     }
 
     fn point_on_path(&self, tower: &Tower) -> bool {
@@ -598,4 +1572,10 @@ impl RectangleInPath {
         }
     }
 
-}
\ No newline at end of file
+}
+
+// a box of the given size in the middle of `area`, smaller if `area` is
+fn popup(area: Rect, width: u16, height: u16) -> Rect {
+    let (width, height) = (width.min(area.width), height.min(area.height));
+    Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height)
+}
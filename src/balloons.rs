@@ -1,28 +1,36 @@
 use {
-    crate::app::BalloonPath, 
+    crate::{
+        app::BalloonPath,
+        difficulty::Difficulty,
+        theme::Theme
+    },
     color_eyre::Result, 
-    rand::{thread_rng, Rng}, 
-    ratatui::{
-        prelude::Color,
-        widgets::canvas::{Circle, Context}
-    }
+    rand::{rngs::StdRng, Rng, SeedableRng}, 
+    serde::{Deserialize, Serialize},
+    ratatui::widgets::canvas::{Circle, Context, Line}
 };
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct Balloon {
+    pub kind: BalloonKind,
     pub x: f64,
     pub y: f64,
     pub radius: f64,
-    pub color: Color,
     pub reward: (u16, u64), // gold, score
     pub damage: u16,
     pub total_x: f64,
     hitpoints: f64,
+    max_hitpoints: f64,
     current_segment: usize,
     last_move: Vec<f64>,
     speed: f64,
+    unshown_damage: f64, // damage that is too little to show on its own yet
+    damage_shown_at: Option<u64>
 }
 
+const DAMAGE_INTERVAL: u64 = 300; // ticks between two damage numbers of the same balloon
+const MIN_SHOWN_DAMAGE: f64 = 0.05; // rounds to 0.1, the precision damage numbers are shown at
+
 impl Balloon {
     pub fn move_balloon(&mut self, path: &BalloonPath) -> Result<bool> {
         /*
@@ -100,6 +108,10 @@ impl Balloon {
         Ok(true)
     }
 
+    pub fn hitpoints(&self) -> f64 {
+        self.hitpoints
+    }
+
     pub fn reduce_hitpoints(&mut self, damge: f64) {
         self.hitpoints -= damge;
     }
@@ -113,13 +125,16 @@ impl Balloon {
 
     pub fn generate_clone(&self) -> Self {
         Balloon {
+            kind: self.kind,
             x: self.x,
             y: self.y,
             radius: self.radius,
-            color: self.color,
             hitpoints: self.hitpoints,
+            max_hitpoints: self.max_hitpoints,
             current_segment: self.current_segment,
             last_move: self.last_move.clone(),
+            unshown_damage: self.unshown_damage,
+            damage_shown_at: self.damage_shown_at,
             reward: self.reward,
             speed: self.speed,
             damage: self.damage,
@@ -127,43 +142,130 @@ impl Balloon {
         }
     }
 
-    pub fn render_self(&self, ctx: &mut Context) {
+    // flames hit every tick for very little, so damage adds up until it is worth a number and the last one had time to float away
+    pub fn damage_to_show(&mut self, damage: f64, tick: u64) -> Option<f64> {
+        self.unshown_damage += damage;
+        if self.unshown_damage < MIN_SHOWN_DAMAGE || self.damage_shown_at.is_some_and(|shown| tick < shown + DAMAGE_INTERVAL) {
+            return None;
+        }
+        self.damage_shown_at = Some(tick);
+        Some(std::mem::take(&mut self.unshown_damage))
+    }
+
+    // the share of the hitpoints that is left, 1.0 for an undamaged balloon
+    pub fn health(&self) -> f64 {
+        if self.max_hitpoints <= 0.0 {
+            return 0.0;
+        }
+        (self.hitpoints / self.max_hitpoints).clamp(0.0, 1.0)
+    }
+
+    pub fn render_self(&self, ctx: &mut Context, theme: &Theme, health_bar: bool) {
         ctx.draw(&Circle {
             x: self.x,
             y: self.y,
             radius: self.radius,
-            color: self.color
+            color: theme.balloon(self.kind, self.health())
         });
+        if health_bar && self.max_hitpoints > 1.0 && self.health() < 1.0 { // single hit balloons don't need one
+            let (left, right, y) = (self.x - self.radius, self.x + self.radius, self.y + self.radius + 2.0);
+            let split = left + (right - left) * self.health();
+            ctx.draw(&Line { x1: left, y1: y, x2: right, y2: y, color: theme.muted });
+            ctx.draw(&Line { x1: left, y1: y, x2: split, y2: y, color: theme.health });
+        }
     }
 
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BalloonKind {
+    #[default]
+    Red,
+    Blue,
+    Blimp
+}
+
+impl BalloonKind {
+    pub const ALL: [BalloonKind; 3] = [BalloonKind::Red, BalloonKind::Blue, BalloonKind::Blimp];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BalloonKind::Red => "red",
+            BalloonKind::Blue => "blue",
+            BalloonKind::Blimp => "blimp"
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BalloonGroup {
+    pub kind: BalloonKind,
+    pub count: usize
+}
+
+// a hand written wave, groups are sent in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaveDefinition {
+    pub ticks_between_balloons: u16,
+    pub groups: Vec<BalloonGroup>
+}
+
+#[derive(Debug)]
 pub struct BalloonFactory {
+    speed_multiplier: f64,
+    hitpoints_multiplier: f64,
+    rng: StdRng, // seeded so that a run can be reproduced
+    waves: Vec<WaveDefinition> // replace the built in waves, later rounds fall back to the generated ones
+}
 
+impl Default for BalloonFactory {
+    fn default() -> Self {
+        Self::new(Difficulty::default(), 0, vec![])
+    }
 }
 
 impl BalloonFactory {
-    pub fn generate_wave(&self, round: usize, x: f64, y: f64) -> BalloonWave {
+    pub fn new(difficulty: Difficulty, seed: u64, waves: Vec<WaveDefinition>) -> Self {
+        Self {
+            speed_multiplier: difficulty.balloon_speed_multiplier(),
+            hitpoints_multiplier: difficulty.balloon_hitpoints_multiplier(),
+            rng: StdRng::seed_from_u64(seed),
+            waves
+        }
+    }
+
+    pub fn generate_wave(&mut self, round: usize, x: f64, y: f64) -> BalloonWave {
+        if let Some(definition) = round.checked_sub(1).and_then(|i| self.waves.get(i)) {
+            return BalloonWave {
+                current: 0,
+                ticks_since_last: 0,
+                ticks_till_balloon: definition.ticks_between_balloons,
+                balloons: definition.groups.iter().flat_map(|group| {
+                    vec![self.balloon(group.kind, x, y); group.count]
+                }).collect()
+            };
+        }
         match round {
             1 => self.wave_1(x, y),
             2 => self.wave_2(x, y),
             3 => self.wave_3(x, y),
             _ => {
-                let mut rng = thread_rng();
+                let mut balloons = vec![];
+                for _ in 0..(round * 20) {
+                    if self.rng.gen_range(0.0..1.0) < (0.9 / round as f64 * 2.0) {
+                        balloons.push(self.red_balloon(x, y));
+                    }
+                    else if round > 4 {
+                        balloons.push(self.blimp(x, y));
+                    }
+                    else {
+                        balloons.push(self.blue_balloon(x, y));
+                    }
+                }
                 BalloonWave {
                     current: 0,
-                    balloons: (0..(round * 20)).map(|_index| {
-                        if rng.gen_range(0.0..1.0) < (0.9 / round as f64 * 2.0) {
-                            self.red_balloon(x, y)
-                        }
-                        else if round > 4 {
-                            self.blimp(x, y)
-                        }
-                        else {
-                            self.blue_balloon(x, y)
-                        }
-                    }).collect(),
+                    balloons,
                     ticks_since_last: 0,
                     ticks_till_balloon: 300
                 }
@@ -171,6 +273,14 @@ impl BalloonFactory {
         }
     }
 
+    pub fn balloon(&self, kind: BalloonKind, x: f64, y: f64) -> Balloon {
+        match kind {
+            BalloonKind::Red => self.red_balloon(x, y),
+            BalloonKind::Blue => self.blue_balloon(x, y),
+            BalloonKind::Blimp => self.blimp(x, y)
+        }
+    }
+
     fn wave_1(&self, x: f64, y: f64) -> BalloonWave {
         BalloonWave {
             current: 0,
@@ -207,14 +317,17 @@ impl BalloonFactory {
 
     fn red_balloon(&self, x: f64, y: f64) -> Balloon {
         Balloon {
+            kind: BalloonKind::Red,
             x: x,
             y: y,
             radius: 5.0,
-            color: Color::Red,
-            hitpoints: 1.0,
+            hitpoints: 1.0 * self.hitpoints_multiplier,
+            max_hitpoints: 1.0 * self.hitpoints_multiplier,
             current_segment: 0,
             last_move: vec![0.0, 0.0],
-            speed: 0.02,
+            unshown_damage: 0.0,
+            damage_shown_at: None,
+            speed: 0.02 * self.speed_multiplier,
             reward: (1, 1),
             damage: 1,
             total_x: 0.0
@@ -223,14 +336,17 @@ impl BalloonFactory {
 
     fn blue_balloon(&self, x: f64, y: f64) -> Balloon {
         Balloon {
+            kind: BalloonKind::Blue,
             x: x,
             y: y,
             radius: 5.0,
-            color: Color::Blue,
-            hitpoints: 2.0,
+            hitpoints: 2.0 * self.hitpoints_multiplier,
+            max_hitpoints: 2.0 * self.hitpoints_multiplier,
             current_segment: 0,
             last_move: vec![0.0, 0.0],
-            speed: 0.03,
+            unshown_damage: 0.0,
+            damage_shown_at: None,
+            speed: 0.03 * self.speed_multiplier,
             reward: (2, 2),
             damage: 2,
             total_x: 0.0
@@ -239,14 +355,17 @@ impl BalloonFactory {
 
     fn blimp(&self, x: f64, y: f64) -> Balloon {
         Balloon {
+            kind: BalloonKind::Blimp,
             x: x,
             y: y,
             radius: 15.0,
-            color: Color::Blue,
-            hitpoints: 100.0,
+            hitpoints: 100.0 * self.hitpoints_multiplier,
+            max_hitpoints: 100.0 * self.hitpoints_multiplier,
             current_segment: 0,
             last_move: vec![0.0, 0.0],
-            speed: 0.02,
+            unshown_damage: 0.0,
+            damage_shown_at: None,
+            speed: 0.02 * self.speed_multiplier,
             reward: (50, 50),
             damage: 50,
             total_x: 0.0
@@ -254,7 +373,7 @@ impl BalloonFactory {
     }
 }
 
-#[derive (Clone, Debug)]
+#[derive (Clone, Debug, Default)]
 pub struct BalloonWave {
     pub ticks_since_last: u16,
     pub ticks_till_balloon: u16,
@@ -262,6 +381,13 @@ pub struct BalloonWave {
     current: usize,
 }
 
+impl BalloonWave {
+    // queues the balloons of another wave behind the ones that are still waiting
+    pub fn append(&mut self, other: BalloonWave) {
+        self.balloons.extend(other.balloons.into_iter().skip(other.current));
+    }
+}
+
 impl Iterator for BalloonWave {
     type Item = Balloon;
 
@@ -0,0 +1,156 @@
+use {
+    clap::Parser,
+    color_eyre::{eyre::eyre, Result},
+    serde::Serialize,
+    std::{fmt::Write as _, fs, path::PathBuf},
+    tower_defense::{
+        app::GameSettings,
+        balloons::WaveDefinition,
+        bots::{play, Bot},
+        difficulty::Difficulty,
+        maps::Map,
+        read_write,
+        simulation::{simulate, Layout, RunResult}
+    }
+};
+
+#[derive(Debug, Parser)]
+#[command(about = "plays a tower layout against the waves for many seeds without a terminal")]
+struct Args {
+    /// json list of towers to build, in build order
+    #[arg(long, value_name = "FILE")]
+    layout: Option<PathBuf>,
+
+    /// lets a bot play instead of building a fixed layout
+    #[arg(long, value_enum, conflicts_with = "layout")]
+    bot: Option<Bot>,
+
+    /// json file describing the balloon path
+    #[arg(long, value_name = "FILE")]
+    map: Option<PathBuf>,
+
+    /// json list of waves replacing the built in ones
+    #[arg(long, value_name = "FILE")]
+    waves: Option<PathBuf>,
+
+    /// number of waves to play per seed
+    #[arg(long, default_value_t = 20)]
+    max_waves: usize,
+
+    /// number of seeds to play, starting at --first-seed
+    #[arg(long, default_value_t = 100)]
+    seeds: u64,
+
+    #[arg(long, default_value_t = 0)]
+    first_seed: u64,
+
+    #[arg(long, value_enum, default_value_t)]
+    difficulty: Difficulty,
+
+    #[arg(long, default_value_t = 1)]
+    start_wave: usize,
+
+    /// ticks between waves
+    #[arg(long, default_value_t = 0)]
+    build_ticks: u32,
+
+    /// writes every run to a .csv or .json file
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>
+}
+
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    difficulty: Difficulty,
+    map: &'a str,
+    max_waves: usize,
+    runs: &'a [RunResult]
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let mut settings = GameSettings {
+        difficulty: args.difficulty,
+        start_wave: args.start_wave.max(1),
+        ..GameSettings::default()
+    };
+    if let Some(path) = args.map.as_ref() {
+        settings.map = Map::load(path)?;
+    }
+    if let Some(path) = args.waves.as_ref() {
+        settings.waves = read_write::read_json::<Vec<WaveDefinition>>(path)?;
+    }
+    let layout: Layout = match args.layout.as_ref() {
+        Some(path) => read_write::read_json(path)?,
+        None => vec![]
+    };
+
+    let mut runs = vec![];
+    for seed in args.first_seed..args.first_seed + args.seeds {
+        settings.seed = seed;
+        let run = match args.bot {
+            Some(bot) => play(&settings, bot.strategy(seed).as_mut(), args.max_waves, args.build_ticks)?,
+            None => simulate(&settings, &layout, args.max_waves, args.build_ticks)?
+        };
+        runs.push(run);
+    }
+
+    print_summary(&runs, args.max_waves);
+    if let Some(path) = args.output.as_ref() {
+        let report = Report {
+            difficulty: args.difficulty,
+            map: &settings.map.name,
+            max_waves: args.max_waves,
+            runs: &runs
+        };
+        export(path, &report)?;
+    }
+    Ok(())
+}
+
+fn print_summary(runs: &[RunResult], max_waves: usize) {
+    if runs.is_empty() {
+        return;
+    }
+    let n = runs.len() as f64;
+    let survived: Vec<usize> = runs.iter().map(|run| run.waves_survived).collect();
+    println!("runs: {} | died: {} | won: {} | skipped steps: {}", runs.len(), runs.iter().filter(|run| run.died_in_wave.is_some()).count(), runs.iter().filter(|run| run.won).count(), runs.iter().map(|run| run.skipped_steps).sum::<usize>());
+    println!("waves survived: avg {:.2} | min {} | max {}", survived.iter().sum::<usize>() as f64 / n, survived.iter().min().unwrap(), survived.iter().max().unwrap());
+    println!("lives lost: avg {:.2} | score: avg {:.1}", runs.iter().map(|run| run.lives_lost as f64).sum::<f64>() / n, runs.iter().map(|run| run.score as f64).sum::<f64>() / n);
+    println!();
+    println!("{:>6}{:>8}{:>12}{:>12}{:>10}", "wave", "runs", "avg leaks", "avg lives", "avg gold");
+    for index in 0..max_waves {
+        let waves: Vec<_> = runs.iter().filter_map(|run| run.waves.get(index)).collect();
+        if waves.is_empty() {
+            break;
+        }
+        let count = waves.len() as f64;
+        println!("{:>6}{:>8}{:>12.2}{:>12.2}{:>10.1}",
+            waves[0].wave,
+            waves.len(),
+            waves.iter().map(|wave| wave.leaks as f64).sum::<f64>() / count,
+            waves.iter().map(|wave| wave.lives_lost as f64).sum::<f64>() / count,
+            waves.iter().map(|wave| wave.gold as f64).sum::<f64>() / count
+        );
+    }
+}
+
+fn export(path: &PathBuf, report: &Report) -> Result<()> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => read_write::save_json(path, report)?,
+        Some("csv") => {
+            let mut csv = String::from("seed,wave,leaks,lives_lost,gold,died\n");
+            for run in report.runs {
+                for wave in run.waves.iter() {
+                    writeln!(csv, "{},{},{},{},{},{}", run.seed, wave.wave, wave.leaks, wave.lives_lost, wave.gold, false)?;
+                }
+                if let Some(wave) = run.died_in_wave {
+                    writeln!(csv, "{},{},,,,{}", run.seed, wave, true)?;
+                }
+            }
+            fs::write(path, csv)?;
+        },
+        _ => return Err(eyre!("unknown output format {}, use .csv or .json", path.display()))
+    }
+    Ok(())
+}
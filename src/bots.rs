@@ -0,0 +1,229 @@
+use {
+    crate::{
+        action::Action,
+        app::{App, GameSettings},
+        observation::{Area, Observation},
+        simulation::{drive, RunResult},
+        towers::TowerKind,
+        utils::distance_in_2d
+    },
+    clap::ValueEnum,
+    color_eyre::Result,
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    serde::{Deserialize, Serialize}
+};
+
+const GRID_STEP: f64 = 5.0;
+
+// an automated player, it looks at the game and answers with the actions it wants to take
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+
+    fn cadence(&self) -> Cadence {
+        Cadence::Wave
+    }
+
+    // actions the game refuses are dropped, the next observation shows what went through
+    fn decide(&mut self, observation: &Observation) -> Vec<Action>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cadence {
+    Ticks(u64), // every n ticks
+    Wave // once between two waves, at the start of the build phase
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Bot {
+    #[default]
+    Greedy,
+    Random
+}
+
+impl Bot {
+    pub fn strategy(&self, seed: u64) -> Box<dyn Strategy> {
+        match self {
+            Bot::Greedy => Box::new(GreedyBot::new(TowerKind::DartThrower)),
+            Bot::Random => Box::new(RandomBot::new(seed))
+        }
+    }
+}
+
+// lets the strategy play until it died or survived max_waves waves
+pub fn play(settings: &GameSettings, strategy: &mut dyn Strategy, max_waves: usize, build_ticks: u32) -> Result<RunResult> {
+    let cadence = strategy.cadence();
+    let mut decided = None; // the last wave the strategy looked at
+    let mut refused = 0;
+    let mut result = drive(settings, max_waves, build_ticks, |app| {
+        if !due(cadence, app, &mut decided) {
+            return;
+        }
+        for action in strategy.decide(&app.observe()) {
+            if !app.act(action) {
+                refused += 1;
+            }
+        }
+    })?;
+    result.skipped_steps = refused;
+    Ok(result)
+}
+
+fn due(cadence: Cadence, app: &App, decided: &mut Option<usize>) -> bool {
+    match cadence {
+        Cadence::Ticks(n) => app.ticks().is_multiple_of(n.max(1)),
+        Cadence::Wave => {
+            // without build ticks the next wave starts right away, then the strategy decides during its first tick
+            let completed = if app.building() { app.round } else { app.round.saturating_sub(1) };
+            if *decided == Some(completed) {
+                return false;
+            }
+            *decided = Some(completed);
+            true
+        }
+    }
+}
+
+// places its tower where it covers the most path and upgrades whenever that is cheaper
+pub struct GreedyBot {
+    kind: TowerKind
+}
+
+impl GreedyBot {
+    pub fn new(kind: TowerKind) -> Self {
+        Self { kind }
+    }
+
+    fn best_spot(&self, observation: &Observation, taken: &[Area]) -> Option<Area> {
+        let entry = observation.shop_entry(self.kind)?;
+        let path = path_points(observation);
+        let field = observation.field;
+        let mut best: Option<(usize, Area)> = None;
+        let mut y = field.y;
+        while y + entry.height <= field.y + field.height {
+            let mut x = field.x;
+            while x + entry.width <= field.x + field.width {
+                let area = Area::new(x, y, entry.width, entry.height);
+                if observation.free(&area) && !taken.iter().any(|other| other.overlaps(&area)) {
+                    let covered = path.iter().filter(|(px, py)| {
+                        distance_in_2d(vec![x, y + entry.height / 2.0], vec![*px, *py]) <= entry.range
+                    }).count();
+                    if best.is_none_or(|(most, _)| covered > most) {
+                        best = Some((covered, area));
+                    }
+                }
+                x += GRID_STEP;
+            }
+            y += GRID_STEP;
+        }
+        best.filter(|(covered, _)| *covered > 0).map(|(_, area)| area)
+    }
+}
+
+impl Strategy for GreedyBot {
+    fn name(&self) -> &'static str {
+        "greedy"
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::Ticks(250)
+    }
+
+    fn decide(&mut self, observation: &Observation) -> Vec<Action> {
+        let Some(tower_cost) = observation.shop_entry(self.kind).map(|entry| entry.cost) else {
+            return vec![];
+        };
+        let mut upgrades: Vec<(u16, usize, usize)> = observation.towers.iter().enumerate().flat_map(|(tower, state)| {
+            state.upgrades.iter().enumerate().filter_map(move |(path, upgrade)| {
+                upgrade.next_cost.map(|cost| (cost, tower, path))
+            })
+        }).collect();
+        upgrades.sort_by_key(|(cost, _, _)| std::cmp::Reverse(*cost)); // cheapest last, so pop takes it first
+
+        let mut gold = observation.gold;
+        let mut taken = vec![];
+        let mut actions = vec![];
+        loop {
+            let upgrade = upgrades.last().copied().filter(|(cost, _, _)| *cost < tower_cost && *cost <= gold);
+            if let Some((cost, tower, path)) = upgrade {
+                upgrades.pop();
+                gold -= cost;
+                actions.push(Action::Upgrade { tower, path });
+                continue;
+            }
+            if gold < tower_cost {
+                break;
+            }
+            let Some(area) = self.best_spot(observation, &taken) else {
+                break;
+            };
+            gold -= tower_cost;
+            taken.push(area);
+            actions.push(Action::PlaceTower { kind: self.kind, x: area.x, y: area.y });
+        }
+        actions
+    }
+}
+
+// buys random things at random free spots, a baseline every other bot should beat
+pub struct RandomBot {
+    rng: StdRng
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed)
+        }
+    }
+}
+
+impl Strategy for RandomBot {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn decide(&mut self, observation: &Observation) -> Vec<Action> {
+        let mut actions = vec![];
+        for _ in 0..self.rng.gen_range(0..4) {
+            if !observation.towers.is_empty() && self.rng.gen_bool(0.5) {
+                let tower = self.rng.gen_range(0..observation.towers.len());
+                let path = self.rng.gen_range(0..observation.towers[tower].upgrades.len().max(1));
+                actions.push(Action::Upgrade { tower, path });
+                continue;
+            }
+            if observation.shop.is_empty() {
+                break;
+            }
+            let entry = observation.shop[self.rng.gen_range(0..observation.shop.len())];
+            let field = observation.field;
+            let x = self.rng.gen_range(field.x..field.x + field.width - entry.width);
+            let y = self.rng.gen_range(field.y..field.y + field.height - entry.height);
+            if observation.free(&Area::new(x, y, entry.width, entry.height)) {
+                actions.push(Action::PlaceTower { kind: entry.kind, x, y });
+            }
+        }
+        if observation.building() && self.rng.gen_bool(0.3) {
+            actions.push(Action::StartWave);
+        }
+        actions
+    }
+}
+
+// points along the middle of every path segment
+fn path_points(observation: &Observation) -> Vec<(f64, f64)> {
+    observation.path.iter().flat_map(|segment| {
+        let (cx, cy) = segment.center();
+        let length = segment.width.max(segment.height);
+        let steps = (length / GRID_STEP).ceil() as usize;
+        (0..=steps).map(move |i| {
+            let along = i as f64 * GRID_STEP;
+            if segment.width >= segment.height {
+                (segment.x + along.min(length), cy)
+            }
+            else {
+                (cx, segment.y + along.min(length))
+            }
+        })
+    }).collect()
+}
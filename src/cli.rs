@@ -0,0 +1,57 @@
+use {
+    crate::{app::GameMode, difficulty::Difficulty},
+    clap::Parser,
+    std::path::PathBuf
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about = "tower defense in the terminal")]
+pub struct Cli {
+    /// seed for the balloon waves, random if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// json file describing the balloon path
+    #[arg(long, value_name = "FILE")]
+    pub map: Option<PathBuf>,
+
+    /// skips the start menu
+    #[arg(long, value_enum)]
+    pub difficulty: Option<Difficulty>,
+
+    /// skips the start menu
+    #[arg(long, value_enum)]
+    pub mode: Option<GameMode>,
+
+    /// wave the game starts at
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub start_wave: u64,
+
+    /// percent of a tower's cost and upgrades that selling it gives back
+    #[arg(long, default_value_t = 70, value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub sell_refund: u8,
+
+    /// where highscores, replays and settings are kept
+    #[arg(long, visible_alias = "data-dir", value_name = "DIR")]
+    pub config_dir: Option<PathBuf>,
+
+    /// plays back a recorded game instead of starting a new one
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// writes per wave statistics to a .csv or .json file when a game ends
+    #[arg(long, value_name = "FILE")]
+    pub telemetry: Option<PathBuf>,
+
+    /// runs the simulation without a terminal and prints the result
+    #[arg(long)]
+    pub headless: bool,
+
+    /// reads json commands from stdin and answers each with a json observation on stdout
+    #[arg(long, conflicts_with_all = ["headless", "replay"])]
+    pub stdio: bool,
+
+    /// stops a headless run after this many waves
+    #[arg(long, default_value_t = 100)]
+    pub max_waves: usize
+}
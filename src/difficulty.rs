@@ -0,0 +1,82 @@
+use {
+    clap::ValueEnum,
+    serde::{Deserialize, Serialize}
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Impossible
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Impossible];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Impossible => "impossible"
+        }
+    }
+
+    // position of the highscore in the save file, normal comes first so old single score files stay valid
+    pub fn storage_index(&self) -> usize {
+        match self {
+            Difficulty::Normal => 0,
+            Difficulty::Easy => 1,
+            Difficulty::Hard => 2,
+            Difficulty::Impossible => 3
+        }
+    }
+
+    pub fn starting_gold(&self) -> u16 {
+        match self {
+            Difficulty::Easy => 50,
+            Difficulty::Normal => 30,
+            Difficulty::Hard => 20,
+            Difficulty::Impossible => 10
+        }
+    }
+
+    pub fn hitpoints(&self) -> u16 {
+        match self {
+            Difficulty::Easy => 200,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 50,
+            Difficulty::Impossible => 1
+        }
+    }
+
+    pub fn cost_multiplier(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.2,
+            Difficulty::Impossible => 1.5
+        }
+    }
+
+    pub fn balloon_speed_multiplier(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.2,
+            Difficulty::Impossible => 1.5
+        }
+    }
+
+    pub fn balloon_hitpoints_multiplier(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+            Difficulty::Impossible => 2.0
+        }
+    }
+}
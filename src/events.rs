@@ -0,0 +1,163 @@
+use {
+    crate::{balloons::BalloonKind, theme::Theme, towers::TowerKind},
+    ratatui::{prelude::Stylize, text},
+    serde::Serialize,
+    std::{cell::RefCell, collections::VecDeque, fmt, rc::Rc}
+};
+
+const MAX_MESSAGES: usize = 100;
+const BANNER_TICKS: u64 = 3000; // how long an announcement stays over the playfield
+
+// everything noteworthy the simulation does, in the order it happened
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    WaveStarted { wave: usize },
+    WaveCleared { wave: usize },
+    BalloonSpawned { kind: BalloonKind },
+    BalloonPopped { kind: BalloonKind, gold: u16, score: u64 },
+    BalloonLeaked { kind: BalloonKind, lives: u16 },
+    TowerBuilt { kind: TowerKind, cost: u16 },
+    TowerUpgraded { kind: TowerKind, upgrade: &'static str, cost: u16 },
+    TowerSold { kind: TowerKind, refund: u16 },
+    EarlyBonus { gold: u16 }, // for calling a wave before the countdown ran out
+    NewHighscore { score: u64 },
+    GameOver { wave: usize },
+    Victory { wave: usize },
+    FreeplayStarted
+}
+
+impl GameEvent {
+    // what the message log says about it, None for what happens too often to be worth a line
+    pub fn message(&self) -> Option<String> {
+        Some(match self {
+            GameEvent::WaveStarted { wave } => format!("wave {wave} started"),
+            GameEvent::WaveCleared { wave } => format!("wave {wave} cleared"),
+            GameEvent::BalloonPopped { kind: BalloonKind::Blimp, gold, .. } => format!("blimp popped, +{gold} $"),
+            GameEvent::BalloonSpawned { .. } | GameEvent::BalloonPopped { .. } => return None,
+            GameEvent::BalloonLeaked { kind, lives } => format!("{} balloon leaked, -{lives} lives", kind.name()),
+            GameEvent::TowerBuilt { kind, cost } => format!("built {} for {cost} $", kind.name()),
+            GameEvent::TowerUpgraded { upgrade, cost, .. } => format!("bought {upgrade} for {cost} $"),
+            GameEvent::TowerSold { kind, refund } => format!("sold {} for {refund} $", kind.name()),
+            GameEvent::EarlyBonus { gold } => format!("called early, +{gold} $"),
+            GameEvent::NewHighscore { .. } => "new highscore!".to_string(),
+            GameEvent::GameOver { wave } => format!("game over in wave {wave}"),
+            GameEvent::Victory { wave } => format!("victory after wave {wave}"),
+            GameEvent::FreeplayStarted => "freeplay started".to_string()
+        })
+    }
+
+    fn good(&self) -> Option<bool> {
+        match self {
+            GameEvent::WaveCleared { .. } | GameEvent::NewHighscore { .. } | GameEvent::Victory { .. } => Some(true),
+            GameEvent::BalloonLeaked { .. } | GameEvent::GameOver { .. } => Some(false),
+            _ => None
+        }
+    }
+}
+
+// anything that wants to hear about events, like the message log or achievements
+pub trait Subscriber {
+    fn notify(&mut self, tick: u64, event: &GameEvent);
+}
+
+// subscribers that are shared are still readable by whoever subscribed them
+impl<T: Subscriber> Subscriber for Rc<RefCell<T>> {
+    fn notify(&mut self, tick: u64, event: &GameEvent) {
+        self.borrow_mut().notify(tick, event);
+    }
+}
+
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Box<dyn Subscriber>>
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EventBus {{ {} subscribers }}", self.subscribers.len())
+    }
+}
+
+impl EventBus {
+    pub fn subscribe(&mut self, subscriber: Box<dyn Subscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    pub fn emit(&mut self, tick: u64, event: GameEvent) {
+        for subscriber in self.subscribers.iter_mut() {
+            subscriber.notify(tick, &event);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Message {
+    tick: u64,
+    text: String,
+    good: Option<bool>,
+    count: u32 // the same message right after each other is shown once
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MessageLog {
+    messages: VecDeque<Message>,
+    announcement: Option<(u64, String)> // the latest one and when it was made
+}
+
+impl Subscriber for MessageLog {
+    fn notify(&mut self, tick: u64, event: &GameEvent) {
+        let Some(text) = event.message() else {
+            return;
+        };
+        self.push(tick, text, event.good());
+    }
+}
+
+impl MessageLog {
+    fn push(&mut self, tick: u64, text: String, good: Option<bool>) {
+        if let Some(last) = self.messages.back_mut().filter(|last| last.text == text) {
+            last.count += 1;
+            last.tick = tick;
+            return;
+        }
+        if self.messages.len() >= MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(Message {
+            tick,
+            text,
+            good,
+            count: 1
+        });
+    }
+
+    // for what doesn't come from the simulation itself, like an unlocked achievement, also shown as a banner for a while
+    pub fn announce(&mut self, tick: u64, text: String) {
+        self.push(tick, text.clone(), Some(true));
+        self.announcement = Some((tick, text));
+    }
+
+    pub fn banner(&self, tick: u64) -> Option<&str> {
+        self.announcement.as_ref()
+            .filter(|(since, _)| tick < since + BANNER_TICKS)
+            .map(|(_, text)| text.as_str())
+    }
+
+    // the latest `rows` messages, oldest first so that new ones come in at the bottom
+    pub fn lines(&self, rows: usize, theme: &Theme) -> Vec<text::Line<'static>> {
+        self.messages.iter().skip(self.messages.len().saturating_sub(rows)).map(|message| {
+            let seconds = message.tick / 1000;
+            let count = if message.count > 1 { format!(" x{}", message.count) } else { String::new() };
+            let color = match message.good {
+                Some(true) => theme.good,
+                Some(false) => theme.bad,
+                None => theme.text
+            };
+            text::Line::from(vec![
+                format!("{:>2}:{:02} ", seconds / 60, seconds % 60).fg(theme.muted),
+                format!("{}{}", message.text, count).fg(color)
+            ])
+        }).collect()
+    }
+}
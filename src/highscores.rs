@@ -0,0 +1,59 @@
+use {
+    crate::difficulty::Difficulty,
+    serde::{Deserialize, Serialize},
+    std::collections::HashMap
+};
+
+pub const HIGHSCORE_FILE: &str = "highscores.json";
+
+pub const MAX_ENTRIES: usize = 10; // per map and difficulty
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighscoreEntry {
+    pub name: String,
+    pub score: u64,
+    pub wave: usize,
+    pub date: String,
+    pub map: String,
+    pub difficulty: Difficulty,
+    pub seed: u64
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct HighscoreTable {
+    entries: Vec<HighscoreEntry>
+}
+
+impl HighscoreTable {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // entries of one map and difficulty, best first
+    pub fn top(&self, map: &str, difficulty: Difficulty) -> Vec<&HighscoreEntry> {
+        self.entries.iter().filter(|entry| entry.map == map && entry.difficulty == difficulty).collect()
+    }
+
+    pub fn best(&self, map: &str, difficulty: Difficulty) -> u64 {
+        self.top(map, difficulty).first().map(|entry| entry.score).unwrap_or(0)
+    }
+
+    pub fn qualifies(&self, map: &str, difficulty: Difficulty, score: u64) -> bool {
+        let top = self.top(map, difficulty);
+        score > 0 && (top.len() < MAX_ENTRIES || top.last().is_some_and(|entry| score > entry.score))
+    }
+
+    // returns the rank of the new entry if it made it into the table
+    pub fn insert(&mut self, entry: HighscoreEntry) -> Option<usize> {
+        let (map, difficulty) = (entry.map.clone(), entry.difficulty);
+        self.entries.push(entry.clone());
+        self.entries.sort_by_key(|e| std::cmp::Reverse(e.score));
+        let mut counts: HashMap<(String, Difficulty), usize> = HashMap::new();
+        self.entries.retain(|e| {
+            let count = counts.entry((e.map.clone(), e.difficulty)).or_insert(0);
+            *count += 1;
+            *count <= MAX_ENTRIES
+        });
+        self.top(&map, difficulty).iter().position(|e| **e == entry)
+    }
+}
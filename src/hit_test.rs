@@ -0,0 +1,58 @@
+use {
+    crate::balloons::BalloonKind,
+    ratatui::layout::Rect,
+    std::cell::RefCell
+};
+
+// everything on the screen that reacts to the mouse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Playfield,
+    ShopItem(usize),
+    SpawnBalloon(BalloonKind),
+    SpawnWave,
+    UpgradePath(usize),
+    SellTower,
+    StartWave,
+    MenuItem(usize),
+    Confirm(bool) // yes or no
+}
+
+// filled while a frame is drawn, so a click is matched against exactly what the player sees
+#[derive(Debug, Default)]
+pub struct HitMap {
+    regions: RefCell<Vec<(Rect, Target)>>
+}
+
+impl HitMap {
+    pub fn clear(&self) {
+        self.regions.borrow_mut().clear();
+    }
+
+    pub fn register(&self, area: Rect, target: Target) {
+        if !area.is_empty() {
+            self.regions.borrow_mut().push((area, target));
+        }
+    }
+
+    // one region per line of a panel, starting at the top of `area`
+    pub fn register_rows(&self, area: Rect, targets: impl IntoIterator<Item = Option<Target>>) {
+        for (row, target) in (area.y..area.bottom()).zip(targets) {
+            if let Some(target) = target {
+                self.register(Rect::new(area.x, row, area.width, 1), target);
+            }
+        }
+    }
+
+    // regions drawn later are on top
+    pub fn hit(&self, col: u16, row: u16) -> Option<(Rect, Target)> {
+        self.regions.borrow().iter().rev().find(|(area, _)| area.contains((col, row).into())).copied()
+    }
+}
+
+// the canvas coordinates (-90..90 on both axes) under a terminal cell of the playfield
+pub fn world_point(playfield: Rect, col: u16, row: u16) -> (f64, f64) {
+    let x = col.saturating_sub(playfield.x) as f64 + 0.5;
+    let y = row.saturating_sub(playfield.y) as f64 + 0.5;
+    (x / playfield.width as f64 * 180.0 - 90.0, 90.0 - y / playfield.height as f64 * 180.0)
+}
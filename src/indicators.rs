@@ -0,0 +1,65 @@
+use {
+    crate::theme::Theme,
+    ratatui::{
+        prelude::Stylize,
+        text::Line,
+        widgets::canvas::Context
+    },
+    std::collections::VecDeque
+};
+
+const LIFETIME: u32 = 600; // ticks
+const RISE: f64 = 8.0; // how far an indicator floats up during its lifetime
+const MAX_INDICATORS: usize = 40; // the oldest ones go first when a lot is hit at once
+
+#[derive(Debug, Clone, PartialEq)]
+struct Indicator {
+    x: f64,
+    y: f64,
+    text: String,
+    pop: bool,
+    ticks_left: u32
+}
+
+// short lived text over the playfield, like the damage a balloon just took
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Indicators {
+    indicators: VecDeque<Indicator>
+}
+
+impl Indicators {
+    pub fn damage(&mut self, x: f64, y: f64, damage: f64) {
+        self.push(x, y, format!("-{}", (damage * 10.0).round() / 10.0), false);
+    }
+
+    pub fn pop(&mut self, x: f64, y: f64) {
+        self.push(x, y, "pop!".to_string(), true);
+    }
+
+    fn push(&mut self, x: f64, y: f64, text: String, pop: bool) {
+        if self.indicators.len() >= MAX_INDICATORS {
+            self.indicators.pop_front();
+        }
+        self.indicators.push_back(Indicator {
+            x,
+            y,
+            text,
+            pop,
+            ticks_left: LIFETIME
+        });
+    }
+
+    pub fn tick(&mut self) {
+        for indicator in self.indicators.iter_mut() {
+            indicator.ticks_left -= 1;
+        }
+        self.indicators.retain(|indicator| indicator.ticks_left > 0);
+    }
+
+    pub fn render(&self, ctx: &mut Context, theme: &Theme) {
+        for indicator in self.indicators.iter() {
+            let age = (LIFETIME - indicator.ticks_left) as f64 / LIFETIME as f64;
+            ctx.print(indicator.x, indicator.y + age * RISE, Line::from(indicator.text.clone().fg(if indicator.pop { theme.pop } else { theme.damage }).bold()));
+        }
+    }
+}
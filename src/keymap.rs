@@ -0,0 +1,227 @@
+use {
+    crossterm::event::KeyCode,
+    serde::{
+        de::{value, IntoDeserializer},
+        Deserialize,
+        Serialize
+    },
+    std::{collections::BTreeMap, fmt, str::FromStr}
+};
+
+pub const KEYMAP_FILE: &str = "keymap.json";
+
+// what a key does in the game and the menus, number keys that aren't bound to anything pick from the shop and panels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyCommand {
+    Quit,
+    Restart,
+    Pause,
+    Menu, // from the game over and victory screens
+    Confirm, // answers yes when asked whether to restart or quit
+    Left,
+    Right,
+    Up,
+    Down,
+    FastLeft,
+    FastRight,
+    FastUp,
+    FastDown,
+    Select,
+    Cancel,
+    Upgrades,
+    Sell,
+    Shop,
+    Log,
+    SpawnPanel,
+    SpawnWave,
+    StartWave,
+    AutoStart,
+    SpeedUp,
+    SpeedDown,
+    Step,
+    Freeplay,
+    Back, // leaves a menu screen
+    Highscores,
+    Mode // switches between normal and sandbox in the main menu
+}
+
+// a key as it is written in the keymap file, like "q", "Enter" or "F5"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Key(pub KeyCode);
+
+const NAMED_KEYS: [(&str, KeyCode); 14] = [
+    ("Esc", KeyCode::Esc),
+    ("Enter", KeyCode::Enter),
+    ("Space", KeyCode::Char(' ')),
+    ("Backspace", KeyCode::Backspace),
+    ("Tab", KeyCode::Tab),
+    ("Delete", KeyCode::Delete),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right)
+];
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if let Some((_, code)) = NAMED_KEYS.iter().find(|(named, _)| named.eq_ignore_ascii_case(name)) {
+            return Ok(Key(*code));
+        }
+        if let Some(number) = name.strip_prefix(['F', 'f']).and_then(|number| number.parse().ok()) {
+            return Ok(Key(KeyCode::F(number)));
+        }
+        let mut chars = name.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(Key(KeyCode::Char(c))),
+            _ => Err(format!("unknown key {name}"))
+        }
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        key.to_string()
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((name, _)) = NAMED_KEYS.iter().find(|(_, code)| *code == self.0) {
+            return write!(f, "{name}");
+        }
+        match self.0 {
+            KeyCode::F(number) => write!(f, "F{number}"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            code => write!(f, "{code:?}")
+        }
+    }
+}
+
+type Bindings = BTreeMap<KeyCommand, Vec<Key>>;
+
+// the keymap file as it was written, so that one typo doesn't make the whole file unreadable
+pub type KeyNames = BTreeMap<String, Vec<String>>;
+
+// every command can have several keys, commands missing from the file keep their default keys
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(into = "Bindings")]
+pub struct Keymap {
+    bindings: Bindings
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = [
+            (KeyCommand::Quit, vec!["q"]),
+            (KeyCommand::Restart, vec!["r"]),
+            (KeyCommand::Pause, vec!["Esc", "p"]),
+            (KeyCommand::Menu, vec!["m", "Esc"]),
+            (KeyCommand::Confirm, vec!["y", "Enter"]),
+            (KeyCommand::Left, vec!["h", "Left"]),
+            (KeyCommand::Right, vec!["l", "Right"]),
+            (KeyCommand::Up, vec!["k", "Up"]),
+            (KeyCommand::Down, vec!["j", "Down"]),
+            (KeyCommand::FastLeft, vec!["H"]),
+            (KeyCommand::FastRight, vec!["L"]),
+            (KeyCommand::FastUp, vec!["K"]),
+            (KeyCommand::FastDown, vec!["J"]),
+            (KeyCommand::Select, vec!["Enter", "Space"]),
+            (KeyCommand::Cancel, vec!["c", "Backspace"]),
+            (KeyCommand::Upgrades, vec!["u"]),
+            (KeyCommand::Sell, vec!["s"]),
+            (KeyCommand::Shop, vec!["t"]),
+            (KeyCommand::Log, vec!["g"]),
+            (KeyCommand::SpawnPanel, vec!["b"]),
+            (KeyCommand::SpawnWave, vec!["w"]),
+            (KeyCommand::StartWave, vec!["n"]),
+            (KeyCommand::AutoStart, vec!["a"]),
+            (KeyCommand::SpeedUp, vec!["+", "="]),
+            (KeyCommand::SpeedDown, vec!["-"]),
+            (KeyCommand::Step, vec!["."]),
+            (KeyCommand::Freeplay, vec!["f", "Enter"]),
+            (KeyCommand::Back, vec!["Esc", "q"]),
+            (KeyCommand::Highscores, vec!["H"]),
+            (KeyCommand::Mode, vec!["m"])
+        ];
+        Self {
+            bindings: bindings.into_iter().map(|(command, keys)| {
+                (command, keys.into_iter().filter_map(|key| key.parse().ok()).collect())
+            }).collect()
+        }
+    }
+}
+
+impl From<Keymap> for Bindings {
+    fn from(keymap: Keymap) -> Self {
+        keymap.bindings
+    }
+}
+
+impl Keymap {
+    // entries that don't parse are skipped with a warning each and keep their default keys
+    pub fn from_names(names: KeyNames) -> (Self, Vec<String>) {
+        let mut keymap = Keymap::default();
+        let mut warnings = vec![];
+        for (command_name, key_names) in names {
+            let Some(command) = parse_command(&command_name) else {
+                warnings.push(format!("{KEYMAP_FILE}: unknown command {command_name}, skipped"));
+                continue;
+            };
+            let mut keys = vec![];
+            for name in key_names.iter() {
+                match name.parse() {
+                    Ok(key) => keys.push(key),
+                    Err(error) => warnings.push(format!("{KEYMAP_FILE}: {error} for {command_name}, skipped"))
+                }
+            }
+            if keys.is_empty() && !key_names.is_empty() {
+                continue; // nothing usable, better the default keys than none
+            }
+            keymap.bindings.insert(command, keys);
+        }
+        (keymap, warnings)
+    }
+
+    // the first of `commands` that is bound to the key, a key can mean different things on different screens
+    pub fn command(&self, code: KeyCode, commands: &[KeyCommand]) -> Option<KeyCommand> {
+        commands.iter().copied().find(|command| {
+            self.bindings.get(command).is_some_and(|keys| keys.contains(&Key(code)))
+        })
+    }
+
+    // the first key of every command for the instruction bar, "hjkl" for single characters and "Up/Down" otherwise
+    pub fn label(&self, commands: &[KeyCommand]) -> String {
+        let keys: Vec<String> = commands.iter()
+            .filter_map(|command| self.bindings.get(command).and_then(|keys| keys.first()))
+            .map(Key::to_string)
+            .collect();
+        if commands.len() > 2 && keys.iter().all(|key| key.chars().count() == 1) {
+            keys.concat()
+        }
+        else {
+            keys.join("/")
+        }
+    }
+}
+
+// the same snake_case names the file uses for commands
+fn parse_command(name: &str) -> Option<KeyCommand> {
+    let deserializer: value::StrDeserializer<value::Error> = name.into_deserializer();
+    KeyCommand::deserialize(deserializer).ok()
+}
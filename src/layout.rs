@@ -0,0 +1,65 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+
+const SIDEBAR_WIDTH: u16 = 36;
+const MIN_PLAYFIELD_WIDTH: u16 = 40;
+
+// how many rows the panels of the sidebar need, None if the panel is closed
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Panels {
+    pub shop: Option<u16>,
+    pub spawn_panel: Option<u16>,
+    pub tower_panel: bool, // takes the rest of the sidebar
+    pub log: Option<u16>
+}
+
+// where everything goes on the terminal, recomputed for every frame so that it follows the size of the terminal
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Screen {
+    pub status: Rect,
+    pub body: Rect,
+    pub playfield: Rect, // including its border
+    pub shop: Option<Rect>,
+    pub spawn_panel: Option<Rect>,
+    pub tower_panel: Option<Rect>,
+    pub log: Option<Rect>
+}
+
+impl Screen {
+    pub fn new(area: Rect, panels: &Panels) -> Self {
+        let [status, body] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+        let mut screen = Screen {
+            status,
+            body,
+            ..Screen::default()
+        };
+        if body.width < SIDEBAR_WIDTH + MIN_PLAYFIELD_WIDTH { // too narrow for the sidebar, the keys still work
+            screen.playfield = square(body);
+            return screen;
+        }
+        let [field, sidebar] = Layout::horizontal([Constraint::Min(0), Constraint::Length(SIDEBAR_WIDTH)]).areas(body);
+        screen.playfield = square(field);
+        let [shop, spawn_panel, tower_panel, log] = Layout::vertical([
+            Constraint::Length(panels.shop.map_or(0, |rows| rows + 2)),
+            Constraint::Length(panels.spawn_panel.map_or(0, |rows| rows + 2)),
+            Constraint::Min(0),
+            Constraint::Length(panels.log.map_or(0, |rows| rows + 2))
+        ]).areas(sidebar);
+        screen.shop = panels.shop.map(|_| shop);
+        screen.spawn_panel = panels.spawn_panel.map(|_| spawn_panel);
+        screen.tower_panel = panels.tower_panel.then_some(tower_panel);
+        screen.log = panels.log.map(|_| log);
+        screen
+    }
+}
+
+// a terminal cell is about twice as high as it is wide, so a square playfield is twice as many columns wide as it has rows
+fn square(area: Rect) -> Rect {
+    let rows = area.height.saturating_sub(2).min(area.width.saturating_sub(2) / 2);
+    let (width, height) = ((rows * 2 + 2).min(area.width), (rows + 2).min(area.height)); // tiny terminals get what is there
+    Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height
+    }
+}
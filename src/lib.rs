@@ -0,0 +1,28 @@
+pub mod errors;
+pub mod tui;
+pub mod app;
+pub mod read_write;
+pub mod towers;
+pub mod balloons;
+pub mod utils;
+pub mod difficulty;
+pub mod menu;
+pub mod highscores;
+pub mod profile;
+pub mod action;
+pub mod maps;
+pub mod replay;
+pub mod cli;
+pub mod simulation;
+pub mod observation;
+pub mod bots;
+pub mod protocol;
+pub mod telemetry;
+pub mod layout;
+pub mod hit_test;
+pub mod settings;
+pub mod indicators;
+pub mod theme;
+pub mod keymap;
+pub mod events;
+pub mod achievements;
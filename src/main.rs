@@ -1,52 +1,240 @@
-pub mod errors;
-pub mod tui;
-pub mod app;
-pub mod read_write;
-pub mod towers;
-pub mod balloons;
-pub mod utils;
-
 use {
-    app::App,
-    read_write::*,
+    tower_defense::{
+        achievements::{AchievementStore, AchievementTracker, ACHIEVEMENTS_FILE},
+        app::{App, GameSettings, Outcome},
+        cli::Cli,
+        difficulty::Difficulty,
+        errors,
+        highscores::{HighscoreEntry, HighscoreTable, HIGHSCORE_FILE},
+        keymap::{KeyNames, Keymap, KEYMAP_FILE},
+        maps::Map,
+        menu::{MainMenu, MenuChoice, NameInput},
+        profile::Profile,
+        protocol,
+        read_write::*,
+        replay::{Replay, SaveGame, REPLAY_FILE, SAVE_FILE},
+        settings::{Settings, MAPS_DIR, SETTINGS_FILE},
+        tui,
+        utils
+    },
+    clap::Parser,
+    rand::{thread_rng, Rng},
     std::{
-        fs::File,
-        env
+        cell::RefCell,
+        path::PathBuf,
+        rc::Rc,
+        env,
+        fs,
+        io
     },
     color_eyre::Result
 };
 
 fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut profile = Profile::locate(cli.config_dir.clone())?;
+
+    let mut settings = GameSettings {
+        start_wave: cli.start_wave as usize,
+        sell_refund: cli.sell_refund as f64 / 100.0,
+        ..Default::default()
+    };
+    if let Some(path) = cli.map.as_ref() {
+        settings.map = Map::load(path)?;
+    }
+    let replay = match cli.replay.as_ref() {
+        Some(path) => Some(Replay::load(path)?),
+        None => None
+    };
+    if let Some(replay) = replay.as_ref() {
+        settings = replay.settings.clone();
+    }
+
+    if cli.stdio {
+        settings.difficulty = cli.difficulty.unwrap_or_default();
+        settings.mode = cli.mode.unwrap_or_default();
+        settings.seed = cli.seed.unwrap_or_default();
+        return protocol::serve(settings, io::stdin().lock(), io::stdout().lock());
+    }
+
+    if cli.headless {
+        if replay.is_none() {
+            settings.difficulty = cli.difficulty.unwrap_or_default();
+            settings.mode = cli.mode.unwrap_or_default();
+            settings.seed = cli.seed.unwrap_or_else(|| thread_rng().gen());
+        }
+        let mut app = App::new(&settings)?;
+        if let Some(replay) = replay.as_ref() {
+            app.play_back(replay);
+        }
+        app.run_headless(cli.max_waves)?;
+        if let Some(path) = cli.telemetry.as_ref() {
+            app.telemetry().export(path, app.ticks())?;
+        }
+        println!("seed: {} | difficulty: {} | mode: {} | map: {}", settings.seed, settings.difficulty.name(), settings.mode.name(), settings.map.name);
+        println!("score: {} | wave: {} | hitpoints: {} | gold: {} | dead: {} | won: {}", app.score, app.round, app.hitpoints(), app.gold(), app.is_over() && !app.won(), app.victory_reached());
+        return Ok(());
+    }
+
     errors::install_hooks()?;
     let mut terminal = tui::init()?;
-   
-    let path_to_self = env::current_exe()?;
+
+    if let Some(replay) = replay {
+        let mut app = App::new(&settings)?;
+        app.theme = profile.load::<Settings>(SETTINGS_FILE)?.theme();
+        app.keymap = Keymap::from_names(profile.load::<KeyNames>(KEYMAP_FILE)?).0;
+        app.play_back(&replay);
+        app.run(&mut terminal)?;
+        tui::restore()?;
+        return Ok(());
+    }
+
+    let mut highscores: HighscoreTable = profile.load(HIGHSCORE_FILE)?;
+    if highscores.is_empty() {
+        if let Some(legacy) = legacy_highscore_path() {
+            import_legacy_highscores(&legacy, &mut highscores)?;
+            profile.save(HIGHSCORE_FILE, &highscores)?;
+        }
+    }
+
+    let mut achievements: AchievementStore = profile.load(ACHIEVEMENTS_FILE)?;
+    let mut preferences: Settings = profile.load(SETTINGS_FILE)?;
+    let keymap_exists = profile.path(KEYMAP_FILE).exists();
+    let (keymap, keymap_warnings) = Keymap::from_names(profile.load(KEYMAP_FILE)?);
+    profile.warnings.extend(keymap_warnings);
+    if !keymap_exists { // written once so that there is a file to edit, never over what the player wrote
+        profile.save(KEYMAP_FILE, &keymap)?;
+    }
+    let (mut maps, mut warnings) = Map::load_dir(&profile.path(MAPS_DIR));
+    maps.insert(0, Map::classic());
+    if cli.map.is_some() { // the map from the command line is selected in the menu
+        maps.retain(|map| map.name != settings.map.name);
+        maps.insert(0, settings.map.clone());
+    }
+    warnings.splice(0..0, profile.warnings.clone());
+    let save_path = profile.path(SAVE_FILE);
+    let mut menu = MainMenu::new(warnings, maps, SaveGame::load(&save_path).ok(), keymap.clone());
+    let mut skip_menu = cli.difficulty.is_some() || cli.mode.is_some();
+
+    'menu: loop {
+        let mut resume = None;
+        if skip_menu {
+            skip_menu = false;
+            settings.difficulty = cli.difficulty.unwrap_or_default();
+            settings.mode = cli.mode.unwrap_or_default();
+        }
+        else {
+            let choice = menu.run(&mut terminal, &highscores, &achievements, &mut preferences)?;
+            profile.save(SETTINGS_FILE, &preferences)?;
+            match choice {
+                MenuChoice::NewGame { map, difficulty, mode } => {
+                    settings.map = map;
+                    settings.difficulty = difficulty;
+                    settings.mode = mode;
+                },
+                MenuChoice::Continue(save) => resume = Some(save),
+                MenuChoice::Quit => break
+            }
+        }
+        settings.wave_settings = preferences.wave_settings();
+
+        loop {
+            let mut app = match resume.take() {
+                Some(save) => App::resume(&save)?,
+                None => {
+                    settings.seed = cli.seed.unwrap_or_else(|| thread_rng().gen());
+                    App::new(&settings)?
+                }
+            };
+            let game = app.replay.settings.clone();
+            app.highscore = highscores.best(&game.map.name, game.difficulty);
+            app.set_speed(preferences.speed);
+            app.show_health_bars = preferences.health_bars;
+            app.show_damage = preferences.damage_numbers;
+            app.theme = preferences.theme();
+            app.keymap = keymap.clone();
+            app.save_path = Some(save_path.clone());
+            // subscribed after a continued game was played back so that nothing counts twice
+            let lives_lost = game.difficulty.hitpoints().saturating_sub(app.hitpoints());
+            let tracker = Rc::new(RefCell::new(AchievementTracker::new(achievements.clone(), game.difficulty, game.mode, game.start_wave, lives_lost as u64, app.log())));
+            app.events.subscribe(Box::new(tracker.clone()));
+            let outcome = app.run(&mut terminal)?;
+            achievements = tracker.borrow().store.clone();
+            profile.save(ACHIEVEMENTS_FILE, &achievements)?;
+            app.replay.save(&profile.path(REPLAY_FILE))?;
+            if let Some(path) = cli.telemetry.as_ref() {
+                app.telemetry().export(path, app.ticks())?;
+            }
+            if app.finished() && SaveGame::load(&save_path).is_ok_and(|save| same_game(&save.replay.settings, &game)) {
+                fs::remove_file(&save_path)?; // a lost or won game can't be continued
+            }
+            menu.set_save(SaveGame::load(&save_path).ok());
+            // saved games count once they are over
+            if app.finished() && app.ranked() && highscores.qualifies(&game.map.name, game.difficulty, app.score) {
+                let name = NameInput::new(&default_player_name(), app.score, preferences.theme(), keymap.clone()).run(&mut terminal)?;
+                highscores.insert(HighscoreEntry {
+                    name,
+                    score: app.score,
+                    wave: app.round,
+                    date: utils::today(),
+                    map: game.map.name.clone(),
+                    difficulty: game.difficulty,
+                    seed: game.seed
+                });
+                profile.save(HIGHSCORE_FILE, &highscores)?;
+            }
+            settings = game; // a restart keeps the settings of the game that was just played
+            match outcome {
+                Outcome::Restart => continue,
+                Outcome::Menu => continue 'menu,
+                Outcome::Quit => break 'menu
+            }
+        }
+    }
+    tui::restore()?;
+    
+    Ok(())
+}
+
+// before the profile directory existed the highscore lived three levels above the executable
+fn legacy_highscore_path() -> Option<PathBuf> {
+    let path_to_self = env::current_exe().ok()?;
     let path = path_to_self
         .parent()
         .and_then(|p| p.parent())
         .and_then(|p|p.parent())
-        .map(|p|p.join("Highscore.bin"))
-        .unwrap();
-    let number: u64;
-    if !path.exists() {
-        File::create(&path)?;
-        number = 0;
-    }
-    else {
-        number = read(&path)?;
-    }
-
-    let mut app = App::new()?;
-    app.highscore = number;
-    while !app.run(&mut terminal)? {
-        save(&path, app.highscore)?;
-        app = App::new()?;
-        let number: u64;
-        number = read(&path)?;
-        app.highscore = number;
-    }
-    save(&path, app.highscore)?;
-    tui::restore()?;
-    
+        .map(|p|p.join("Highscore.bin"))?;
+    path.exists().then_some(path)
+}
+
+// a save belongs to a game if it was started with the same settings
+fn same_game(save: &GameSettings, game: &GameSettings) -> bool {
+    save.seed == game.seed && save.map.name == game.map.name && save.difficulty == game.difficulty && save.mode == game.mode
+}
+
+fn default_player_name() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "player".to_string())
+}
+
+// scores from the old Highscore.bin only know their difficulty
+fn import_legacy_highscores(path: &PathBuf, highscores: &mut HighscoreTable) -> Result<()> {
+    let Ok(scores) = read(path) else {
+        return Ok(());
+    };
+    for difficulty in Difficulty::ALL {
+        if let Some(score) = scores.get(difficulty.storage_index()).filter(|score| **score > 0) {
+            highscores.insert(HighscoreEntry {
+                name: "unknown".to_string(),
+                score: *score,
+                wave: 0,
+                date: String::new(),
+                map: Map::classic().name,
+                difficulty,
+                seed: 0
+            });
+        }
+    }
     Ok(())
-}
\ No newline at end of file
+}
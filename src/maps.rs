@@ -0,0 +1,73 @@
+use {
+    crate::read_write,
+    serde::{Deserialize, Serialize},
+    std::{fs, io, path::PathBuf}
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Segment {
+    Horizontal { x0: f64, x1: f64, y: f64 },
+    Vertical { y0: f64, y1: f64, x: f64 }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Map {
+    pub name: String,
+    pub segments: Vec<Segment>,
+    #[serde(default)]
+    pub final_wave: Option<usize> // surviving it wins the game, without one the waves never end
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+impl Map {
+    pub fn classic() -> Self {
+        Self {
+            name: "classic".to_string(),
+            segments: vec![
+                Segment::Horizontal { x0: -90.0, x1: 0.0, y: 0.0 },
+                Segment::Vertical { y0: 0.0, y1: 40.0, x: 0.0 },
+                Segment::Horizontal { x0: -45.0, x1: 0.0, y: 30.0 },
+                Segment::Vertical { y0: -10.0, y1: 40.0, x: -45.0 },
+                Segment::Horizontal { x0: -45.0, x1: 90.0, y: -10.0 }
+            ],
+            final_wave: Some(20)
+        }
+    }
+
+    pub fn load(path: &PathBuf) -> io::Result<Self> {
+        let map: Map = read_write::read_json(path)?;
+        map.check().map_err(|error| io::Error::new(error.kind(), format!("{}: {error}", path.display())))?;
+        Ok(map)
+    }
+
+    // balloons enter on the first segment moving right, so it has to be horizontal
+    pub fn check(&self) -> io::Result<()> {
+        match self.segments.first() {
+            Some(Segment::Horizontal { .. }) => Ok(()),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("map {} has to start with a horizontal segment", self.name)))
+        }
+    }
+
+    // every .json file in the directory, files that can't be read are reported instead
+    pub fn load_dir(dir: &PathBuf) -> (Vec<Map>, Vec<String>) {
+        let (mut maps, mut warnings) = (vec![], vec![]);
+        let Ok(entries) = fs::read_dir(dir) else {
+            return (maps, warnings);
+        };
+        let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+        paths.sort();
+        for path in paths.iter().filter(|path| path.extension().is_some_and(|extension| extension == "json")) {
+            match Map::load(path) {
+                Ok(map) => maps.push(map),
+                Err(error) => warnings.push(format!("map {} could not be loaded: {error}", path.display()))
+            }
+        }
+        (maps, warnings)
+    }
+}
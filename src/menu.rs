@@ -0,0 +1,512 @@
+use {
+    crate::{
+        achievements::{Achievement, AchievementStore},
+        app::GameMode,
+        difficulty::Difficulty,
+        highscores::HighscoreTable,
+        keymap::{KeyCommand, Keymap},
+        maps::Map,
+        replay::SaveGame,
+        settings::Settings,
+        theme::Theme,
+        tui
+    },
+    color_eyre::Result,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    ratatui::{
+        prelude::{text, Alignment, Buffer, Frame, Rect, Stylize, Widget},
+        widgets::{
+            block::{Block, Position, Title},
+            Borders,
+            Paragraph
+        }
+    }
+};
+
+const MAIN_MENU_KEYS: [KeyCommand; 8] = [KeyCommand::Back, KeyCommand::Up, KeyCommand::Down, KeyCommand::Left, KeyCommand::Right, KeyCommand::Highscores, KeyCommand::Mode, KeyCommand::Select];
+const SETTINGS_KEYS: [KeyCommand; 6] = [KeyCommand::Back, KeyCommand::Up, KeyCommand::Down, KeyCommand::Left, KeyCommand::Right, KeyCommand::Select];
+const HIGHSCORE_KEYS: [KeyCommand; 5] = [KeyCommand::Back, KeyCommand::Select, KeyCommand::Highscores, KeyCommand::Left, KeyCommand::Right];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuItem {
+    NewGame,
+    Continue,
+    Map,
+    Difficulty,
+    Mode,
+    Highscores,
+    Achievements,
+    Settings,
+    Quit
+}
+
+impl MenuItem {
+    const ALL: [MenuItem; 9] = [MenuItem::NewGame, MenuItem::Continue, MenuItem::Map, MenuItem::Difficulty, MenuItem::Mode, MenuItem::Highscores, MenuItem::Achievements, MenuItem::Settings, MenuItem::Quit];
+
+    fn label(&self) -> &'static str {
+        match self {
+            MenuItem::NewGame => "new game",
+            MenuItem::Continue => "continue",
+            MenuItem::Map => "map",
+            MenuItem::Difficulty => "difficulty",
+            MenuItem::Mode => "mode",
+            MenuItem::Highscores => "highscores",
+            MenuItem::Achievements => "achievements",
+            MenuItem::Settings => "settings",
+            MenuItem::Quit => "quit"
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MenuChoice {
+    NewGame { map: Map, difficulty: Difficulty, mode: GameMode },
+    Continue(SaveGame),
+    Quit
+}
+
+// kept around between games so that it remembers what was picked last time
+#[derive(Debug)]
+pub struct MainMenu {
+    warnings: Vec<String>,
+    maps: Vec<Map>,
+    save: Option<SaveGame>,
+    selected: usize,
+    map: usize,
+    difficulty: usize,
+    mode: GameMode,
+    choice: Option<MenuChoice>,
+    theme: Theme,
+    keymap: Keymap
+}
+
+impl Widget for &MainMenu {
+    fn render(self, area: Rect, buf: &mut Buffer)
+        where
+            Self: Sized {
+
+                let instructions = Title::from(text::Line::from(vec![
+                    " select:".bold(),
+                    key(&self.keymap, &[KeyCommand::Up, KeyCommand::Down]).bold(),
+                    " change:".bold(),
+                    key(&self.keymap, &[KeyCommand::Left, KeyCommand::Right]).bold(),
+                    " highscores:".bold(),
+                    key(&self.keymap, &[KeyCommand::Highscores]).bold(),
+                    " ok:".bold(),
+                    key(&self.keymap, &[KeyCommand::Select]).bold(),
+                    " exit:".bold(),
+                    key(&self.keymap, &[KeyCommand::Back]).bold()
+                ]));
+
+                let block = Block::default()
+                    .borders(Borders::NONE)
+                    .title(Title::from(" tower defense ".bold())
+                        .alignment(Alignment::Center)
+                        .position(Position::Top))
+                    .title(instructions
+                        .alignment(Alignment::Center)
+                        .position(Position::Bottom))
+                    .bg(self.theme.background);
+
+                let mut lines: Vec<text::Line> = self.warnings.iter().map(|warning| {
+                    text::Line::from(format!("warning: {warning}")).fg(self.theme.bad)
+                }).collect();
+                if !lines.is_empty() {
+                    lines.push(text::Line::from(""));
+                }
+                for (i, item) in MenuItem::ALL.iter().enumerate() {
+                    let value = match item {
+                        MenuItem::Map => format!("< {} >", self.maps[self.map].name),
+                        MenuItem::Difficulty => format!("< {} >", Difficulty::ALL[self.difficulty].name()),
+                        MenuItem::Mode => format!("< {} >", self.mode.name()),
+                        _ => String::new()
+                    };
+                    let line = text::Line::from(format!("{} {:<12}{:<16}", if i == self.selected { ">" } else { " " }, item.label(), value));
+                    lines.push(if *item == MenuItem::Continue && self.save.is_none() {
+                        line.fg(self.theme.muted)
+                    }
+                    else if i == self.selected {
+                        line.fg(self.theme.highlight).bold()
+                    }
+                    else {
+                        line
+                    });
+                }
+                let difficulty = Difficulty::ALL[self.difficulty];
+                lines.push(text::Line::from(""));
+                lines.push(text::Line::from(format!("gold {}  lives {}  costs x{:.1}  balloons x{:.1}", difficulty.starting_gold(), difficulty.hitpoints(), difficulty.cost_multiplier(), difficulty.balloon_hitpoints_multiplier())).fg(self.theme.muted));
+
+                Paragraph::new(lines)
+                    .centered()
+                    .block(block)
+                    .render(area, buf);
+    }
+}
+
+impl MainMenu {
+    // the first map is selected at the start, there has to be at least one
+    pub fn new(warnings: Vec<String>, maps: Vec<Map>, save: Option<SaveGame>, keymap: Keymap) -> Self {
+        Self {
+            warnings,
+            maps,
+            save,
+            selected: 0,
+            map: 0,
+            difficulty: Difficulty::ALL.iter().position(|d| *d == Difficulty::default()).unwrap_or(0),
+            mode: GameMode::default(),
+            choice: None,
+            theme: Theme::default(),
+            keymap
+        }
+    }
+
+    pub fn set_save(&mut self, save: Option<SaveGame>) {
+        self.save = save;
+    }
+
+    pub fn run(&mut self, terminal: &mut tui::Tui, highscores: &HighscoreTable, achievements: &AchievementStore, settings: &mut Settings) -> Result<MenuChoice> {
+        self.choice = None;
+        while self.choice.is_none() {
+            self.theme = settings.theme();
+            terminal.draw(|frame| self.render_frame(frame))?;
+            if let Event::Key(key_event) = event::read()? {
+                if key_event.kind != KeyEventKind::Press {
+                    continue;
+                }
+                let Some(command) = self.keymap.command(key_event.code, &MAIN_MENU_KEYS) else {
+                    continue;
+                };
+                match command {
+                    KeyCommand::Back => self.choice = Some(MenuChoice::Quit),
+                    KeyCommand::Up => self.selected = self.selected.saturating_sub(1),
+                    KeyCommand::Down => self.selected = (self.selected + 1).min(MenuItem::ALL.len() - 1),
+                    KeyCommand::Left => self.change(false),
+                    KeyCommand::Right => self.change(true),
+                    KeyCommand::Highscores => self.show_highscores(terminal, highscores)?,
+                    KeyCommand::Mode => self.mode = toggle(self.mode),
+                    KeyCommand::Select => match MenuItem::ALL[self.selected] {
+                        MenuItem::NewGame => self.choice = Some(MenuChoice::NewGame {
+                            map: self.maps[self.map].clone(),
+                            difficulty: Difficulty::ALL[self.difficulty],
+                            mode: self.mode
+                        }),
+                        MenuItem::Continue => self.choice = self.save.clone().map(MenuChoice::Continue),
+                        MenuItem::Map | MenuItem::Difficulty | MenuItem::Mode => self.change(true),
+                        MenuItem::Highscores => self.show_highscores(terminal, highscores)?,
+                        MenuItem::Achievements => AchievementScreen::new(self.theme, self.keymap.clone()).run(terminal, achievements)?,
+                        MenuItem::Settings => SettingsScreen::new(settings, &self.keymap).run(terminal)?,
+                        MenuItem::Quit => self.choice = Some(MenuChoice::Quit)
+                    },
+                    _ => {}
+                }
+            }
+        }
+        Ok(self.choice.take().unwrap_or(MenuChoice::Quit))
+    }
+
+    fn change(&mut self, forward: bool) {
+        let step = |index: usize, len: usize| if forward { (index + 1) % len } else { (index + len - 1) % len };
+        match MenuItem::ALL[self.selected] {
+            MenuItem::Map => self.map = step(self.map, self.maps.len()),
+            MenuItem::Difficulty => self.difficulty = step(self.difficulty, Difficulty::ALL.len()),
+            MenuItem::Mode => self.mode = toggle(self.mode),
+            _ => {}
+        }
+    }
+
+    fn show_highscores(&self, terminal: &mut tui::Tui, highscores: &HighscoreTable) -> Result<()> {
+        HighscoreScreen::new(&self.maps[self.map].name, Difficulty::ALL[self.difficulty], self.theme, self.keymap.clone()).run(terminal, highscores)
+    }
+
+    fn render_frame(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.size());
+    }
+}
+
+// the keys of the commands as the instruction bars show them
+fn key(keymap: &Keymap, commands: &[KeyCommand]) -> String {
+    format!(" <{}> ", keymap.label(commands))
+}
+
+fn toggle(mode: GameMode) -> GameMode {
+    match mode {
+        GameMode::Normal => GameMode::Sandbox,
+        GameMode::Sandbox => GameMode::Normal
+    }
+}
+
+pub struct SettingsScreen<'a> {
+    settings: &'a mut Settings,
+    keymap: &'a Keymap,
+    selected: usize,
+    exit: bool
+}
+
+impl<'a> SettingsScreen<'a> {
+    pub fn new(settings: &'a mut Settings, keymap: &'a Keymap) -> Self {
+        Self {
+            settings,
+            keymap,
+            selected: 0,
+            exit: false
+        }
+    }
+
+    pub fn run(&mut self, terminal: &mut tui::Tui) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(self.widget(), frame.size()))?;
+            if let Event::Key(key_event) = event::read()? {
+                if key_event.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match self.keymap.command(key_event.code, &SETTINGS_KEYS) {
+                    Some(KeyCommand::Back) => self.exit = true,
+                    Some(KeyCommand::Up) => self.selected = self.selected.saturating_sub(1),
+                    Some(KeyCommand::Down) => self.selected = (self.selected + 1).min(Settings::ROWS - 1),
+                    Some(KeyCommand::Left) => self.settings.change(self.selected, false),
+                    Some(KeyCommand::Right | KeyCommand::Select) => self.settings.change(self.selected, true),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn widget(&self) -> Paragraph<'static> {
+        let theme = self.settings.theme(); // changes show right away
+        let instructions = Title::from(text::Line::from(vec![
+            " select:".bold(),
+            key(self.keymap, &[KeyCommand::Up, KeyCommand::Down]).bold(),
+            " change:".bold(),
+            key(self.keymap, &[KeyCommand::Left, KeyCommand::Right]).bold(),
+            " back:".bold(),
+            key(self.keymap, &[KeyCommand::Back]).bold()
+        ]));
+
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .title(Title::from(" settings ".bold())
+                .alignment(Alignment::Center)
+                .position(Position::Top))
+            .title(instructions
+                .alignment(Alignment::Center)
+                .position(Position::Bottom))
+            .bg(theme.background);
+
+        let lines: Vec<text::Line> = (0..Settings::ROWS).map(|i| {
+            let (label, value) = self.settings.row(i);
+            let line = text::Line::from(format!("{} {:<28}< {} >", if i == self.selected { ">" } else { " " }, label, value));
+            if i == self.selected {
+                line.fg(theme.highlight).bold()
+            }
+            else {
+                line.fg(theme.text)
+            }
+        }).collect();
+        Paragraph::new(lines)
+            .centered()
+            .block(block)
+    }
+}
+
+#[derive(Debug)]
+pub struct HighscoreScreen {
+    map: String,
+    difficulty: usize,
+    exit: bool,
+    theme: Theme,
+    keymap: Keymap
+}
+
+impl HighscoreScreen {
+    pub fn new(map: &str, difficulty: Difficulty, theme: Theme, keymap: Keymap) -> Self {
+        Self {
+            map: map.to_string(),
+            difficulty: Difficulty::ALL.iter().position(|d| *d == difficulty).unwrap_or(0),
+            exit: false,
+            theme,
+            keymap
+        }
+    }
+
+    pub fn run(&mut self, terminal: &mut tui::Tui, highscores: &HighscoreTable) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(self.widget(highscores), frame.size()))?;
+            if let Event::Key(key_event) = event::read()? {
+                if key_event.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match self.keymap.command(key_event.code, &HIGHSCORE_KEYS) {
+                    Some(KeyCommand::Back | KeyCommand::Select | KeyCommand::Highscores) => self.exit = true,
+                    Some(KeyCommand::Left) => self.difficulty = self.difficulty.saturating_sub(1),
+                    Some(KeyCommand::Right) => self.difficulty = (self.difficulty + 1).min(Difficulty::ALL.len() - 1),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn widget(&self, highscores: &HighscoreTable) -> Paragraph<'static> {
+        let instructions = Title::from(text::Line::from(vec![
+            " difficulty:".bold(),
+            key(&self.keymap, &[KeyCommand::Left, KeyCommand::Right]).bold(),
+            " back:".bold(),
+            key(&self.keymap, &[KeyCommand::Back]).bold()
+        ]));
+
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .title(Title::from(" highscores ".bold())
+                .alignment(Alignment::Center)
+                .position(Position::Top))
+            .title(instructions
+                .alignment(Alignment::Center)
+                .position(Position::Bottom))
+            .bg(self.theme.background);
+
+        let difficulty = Difficulty::ALL[self.difficulty];
+        let mut lines = vec![
+            text::Line::from(vec!["map: ".bold(), self.map.clone().into(), " | difficulty: ".bold(), difficulty.name().fg(self.theme.highlight)]),
+            text::Line::from(""),
+            text::Line::from(format!("{:>3}  {:<16}{:>10}{:>6}  {:<12}{:>22}", "#", "name", "score", "wave", "date", "seed")).bold()
+        ];
+        let top = highscores.top(&self.map, difficulty);
+        if top.is_empty() {
+            lines.push(text::Line::from("no scores yet"));
+        }
+        for (i, entry) in top.iter().enumerate() {
+            lines.push(text::Line::from(format!("{:>3}  {:<16}{:>10}{:>6}  {:<12}{:>22}", i + 1, entry.name, entry.score, entry.wave, entry.date, entry.seed)));
+        }
+        Paragraph::new(lines)
+            .centered()
+            .block(block)
+    }
+}
+
+#[derive(Debug)]
+pub struct AchievementScreen {
+    exit: bool,
+    theme: Theme,
+    keymap: Keymap
+}
+
+impl AchievementScreen {
+    pub fn new(theme: Theme, keymap: Keymap) -> Self {
+        Self {
+            exit: false,
+            theme,
+            keymap
+        }
+    }
+
+    pub fn run(&mut self, terminal: &mut tui::Tui, achievements: &AchievementStore) -> Result<()> {
+        while !self.exit {
+            terminal.draw(|frame| frame.render_widget(self.widget(achievements), frame.size()))?;
+            if let Event::Key(key_event) = event::read()? {
+                if key_event.kind != KeyEventKind::Press {
+                    continue;
+                }
+                if self.keymap.command(key_event.code, &[KeyCommand::Back, KeyCommand::Select]).is_some() {
+                    self.exit = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn widget(&self, achievements: &AchievementStore) -> Paragraph<'static> {
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .title(Title::from(" achievements ".bold())
+                .alignment(Alignment::Center)
+                .position(Position::Top))
+            .title(Title::from(text::Line::from(vec![" back:".bold(), key(&self.keymap, &[KeyCommand::Back]).bold()]))
+                .alignment(Alignment::Center)
+                .position(Position::Bottom))
+            .bg(self.theme.background);
+
+        let mut lines = vec![
+            text::Line::from(format!("{} of {} unlocked", achievements.unlocked.len(), Achievement::ALL.len())).fg(self.theme.muted),
+            text::Line::from("")
+        ];
+        for achievement in Achievement::ALL {
+            // locked ones show how far along they are if they count something
+            let status = match (achievements.unlocked.get(&achievement), achievements.progress(achievement)) {
+                (Some(date), _) => date.clone(),
+                (None, Some((done, goal))) => format!("{done}/{goal}"),
+                (None, None) => "locked".to_string()
+            };
+            let line = text::Line::from(format!("{:<14}{:<34}{:>12}", achievement.name(), achievement.description(), status));
+            lines.push(if achievements.unlocked.contains_key(&achievement) { line.fg(self.theme.good).bold() } else { line.fg(self.theme.muted) });
+        }
+        Paragraph::new(lines)
+            .centered()
+            .block(block)
+    }
+}
+
+#[derive(Debug)]
+pub struct NameInput {
+    name: String,
+    score: u64,
+    done: bool,
+    theme: Theme,
+    keymap: Keymap
+}
+
+impl NameInput {
+    pub fn new(default_name: &str, score: u64, theme: Theme, keymap: Keymap) -> Self {
+        Self {
+            name: default_name.to_string(),
+            score,
+            done: false,
+            theme,
+            keymap
+        }
+    }
+
+    pub fn run(&mut self, terminal: &mut tui::Tui) -> Result<String> {
+        while !self.done {
+            terminal.draw(|frame| frame.render_widget(self.widget(), frame.size()))?;
+            if let Event::Key(key_event) = event::read()? {
+                if key_event.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key_event.code {
+                    // characters always go into the name, even if they are bound to something
+                    code if !matches!(code, KeyCode::Char(_)) && self.keymap.command(code, &[KeyCommand::Select, KeyCommand::Back]).is_some() => self.done = true,
+                    KeyCode::Backspace => {
+                        self.name.pop();
+                    },
+                    KeyCode::Char(c) if self.name.chars().count() < 16 => self.name.push(c),
+                    _ => {}
+                }
+            }
+        }
+        if self.name.trim().is_empty() {
+            return Ok("player".to_string());
+        }
+        Ok(self.name.trim().to_string())
+    }
+
+    fn widget(&self) -> Paragraph<'static> {
+        let block = Block::default()
+            .borders(Borders::NONE)
+            .title(Title::from(" new highscore ".bold())
+                .alignment(Alignment::Center)
+                .position(Position::Top))
+            .title(Title::from(text::Line::from(vec![" save:".bold(), key(&self.keymap, &[KeyCommand::Select]).bold()]))
+                .alignment(Alignment::Center)
+                .position(Position::Bottom))
+            .bg(self.theme.background);
+
+        Paragraph::new(vec![
+            text::Line::from(vec!["score: ".bold(), self.score.to_string().fg(self.theme.highlight)]),
+            text::Line::from(""),
+            text::Line::from(vec!["name: ".bold(), self.name.clone().into(), "_".slow_blink()])
+        ])
+            .centered()
+            .block(block)
+    }
+}
@@ -0,0 +1,103 @@
+use {
+    crate::towers::TowerKind,
+    serde::Serialize
+};
+
+// the part of the game state a bot or an external agent gets to see, everything is in canvas coordinates
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Observation {
+    pub tick: u64,
+    pub wave: usize,
+    pub gold: u16,
+    pub hitpoints: u16,
+    pub score: u64,
+    pub dead: bool,
+    pub won: bool,
+    pub final_wave: Option<usize>,
+    pub build_ticks_left: Option<u32>, // Some between waves
+    pub balloons: Vec<BalloonState>,
+    pub towers: Vec<TowerState>,
+    pub shop: Vec<ShopEntry>,
+    pub path: Vec<Area>,
+    pub field: Area // where towers can be placed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BalloonState {
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+    pub hitpoints: f64,
+    pub progress: f64 // distance travelled along the path
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TowerState {
+    pub kind: TowerKind,
+    pub area: Area,
+    pub range: f64,
+    pub sell_value: u16,
+    pub upgrades: Vec<PathState>
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathState {
+    pub name: &'static str,
+    pub level: usize,
+    pub next_cost: Option<u16> // None if the next tier is locked or the path is maxed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ShopEntry {
+    pub kind: TowerKind,
+    pub cost: u16,
+    pub range: f64,
+    pub width: f64,
+    pub height: f64
+}
+
+// an axis aligned rectangle with its lower left corner at (x, y)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Area {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64
+}
+
+impl Area {
+    // also accepts negative sizes, like the ones path segments going left or down have
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x: x.min(x + width),
+            y: y.min(y + height),
+            width: width.abs(),
+            height: height.abs()
+        }
+    }
+
+    pub fn overlaps(&self, other: &Area) -> bool {
+        self.x <= other.x + other.width && other.x <= self.x + self.width
+            && self.y <= other.y + other.height && other.y <= self.y + self.height
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+impl Observation {
+    pub fn building(&self) -> bool {
+        self.build_ticks_left.is_some()
+    }
+
+    pub fn shop_entry(&self, kind: TowerKind) -> Option<&ShopEntry> {
+        self.shop.iter().find(|entry| entry.kind == kind)
+    }
+
+    // stricter than the check of the game, a spot that is free here can always be built on
+    pub fn free(&self, area: &Area) -> bool {
+        !self.path.iter().any(|segment| segment.overlaps(area))
+            && !self.towers.iter().any(|tower| tower.area.overlaps(area))
+    }
+}
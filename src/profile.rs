@@ -0,0 +1,59 @@
+use {
+    crate::read_write,
+    serde::{de::DeserializeOwned, Serialize},
+    std::{
+        env,
+        fs,
+        io,
+        path::PathBuf
+    }
+};
+
+pub const DATA_DIR_VAR: &str = "TOWER_DEFENSE_DATA_DIR";
+
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub dir: PathBuf,
+    pub warnings: Vec<String> // shown to the player on the start screen
+}
+
+impl Profile {
+    // the cli flag wins over the environment variable, which wins over the platform data directory
+    pub fn locate(dir_override: Option<PathBuf>) -> io::Result<Self> {
+        let dir = dir_override
+            .or_else(|| env::var_os(DATA_DIR_VAR).map(PathBuf::from))
+            .or_else(|| dirs::data_dir().map(|dir| dir.join("tower_defense")))
+            .unwrap_or_else(|| PathBuf::from(".tower_defense"));
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            warnings: vec![]
+        })
+    }
+
+    pub fn path(&self, file: &str) -> PathBuf {
+        self.dir.join(file)
+    }
+
+    // a missing file gives the default, a broken one is moved aside to <file>.bak and reset with a warning
+    pub fn load<T: Default + DeserializeOwned>(&mut self, file: &str) -> io::Result<T> {
+        let path = self.path(file);
+        if !path.exists() {
+            return Ok(T::default());
+        }
+        match read_write::read_json(&path) {
+            Ok(value) => Ok(value),
+            Err(error) if error.kind() == io::ErrorKind::InvalidData || error.kind() == io::ErrorKind::UnexpectedEof => {
+                let backup = self.path(&format!("{file}.bak"));
+                fs::rename(&path, &backup)?;
+                self.warnings.push(format!("{file} was corrupt ({error}), it was reset and the old file kept as {}", backup.display()));
+                Ok(T::default())
+            }
+            Err(error) => Err(error)
+        }
+    }
+
+    pub fn save<T: Serialize>(&self, file: &str, value: &T) -> io::Result<()> {
+        read_write::save_json(&self.path(file), value)
+    }
+}
@@ -0,0 +1,145 @@
+use {
+    crate::{
+        action::Action,
+        app::{App, GameMode, GameSettings, WaveSettings},
+        difficulty::Difficulty,
+        observation::Observation
+    },
+    color_eyre::Result,
+    serde::{Deserialize, Serialize},
+    std::io::{BufRead, Write}
+};
+
+// penalty per hitpoint lost, the score of the popped balloons is the positive part of the reward
+const HITPOINT_PENALTY: f64 = 1.0;
+
+// one command per line on stdin, every command is answered with exactly one line on stdout, for example
+// {"command": "reset", "seed": 7, "build_ticks": 0}
+// {"command": "act", "action": "place_tower", "kind": "dart_thrower", "x": -20.0, "y": 15.0}
+// {"command": "step", "ticks": 1000}
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    Reset {
+        seed: Option<u64>,
+        difficulty: Option<Difficulty>,
+        mode: Option<GameMode>,
+        build_ticks: Option<u32> // ticks between waves, waves start right away with 0
+    },
+    Step {
+        #[serde(default = "one")]
+        ticks: u64
+    },
+    Act {
+        #[serde(flatten)]
+        action: Action
+    },
+    Observe,
+    Close
+}
+
+fn one() -> u64 {
+    1
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub observation: Observation,
+    pub reward: f64, // since the last response
+    pub done: bool,
+    pub ticks: u64, // how many ticks the command simulated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted: Option<bool> // whether the game took the action of an act command
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String
+}
+
+// serves commands until stdin is closed or a close command arrives, nothing is drawn
+pub fn serve(settings: GameSettings, input: impl BufRead, mut output: impl Write) -> Result<()> {
+    let mut session = Session::new(settings)?;
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let command = match serde_json::from_str::<Command>(&line) {
+            Ok(command) => command,
+            Err(error) => {
+                serde_json::to_writer(&mut output, &ErrorResponse { error: error.to_string() })?;
+                writeln!(output)?;
+                output.flush()?;
+                continue;
+            }
+        };
+        if command == Command::Close {
+            break;
+        }
+        let response = session.handle(command)?;
+        serde_json::to_writer(&mut output, &response)?;
+        writeln!(output)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+struct Session {
+    settings: GameSettings,
+    wave_settings: WaveSettings,
+    app: App,
+    score: u64, // at the last response, to compute the reward
+    hitpoints: u16
+}
+
+impl Session {
+    fn new(settings: GameSettings) -> Result<Self> {
+        let app = App::new(&settings)?;
+        Ok(Self {
+            score: app.score,
+            hitpoints: app.hitpoints(),
+            wave_settings: WaveSettings::default(),
+            settings,
+            app
+        })
+    }
+
+    fn handle(&mut self, command: Command) -> Result<Response> {
+        let mut ticks = 0;
+        let mut accepted = None;
+        match command {
+            Command::Reset { seed, difficulty, mode, build_ticks } => {
+                self.settings.seed = seed.unwrap_or(self.settings.seed);
+                self.settings.difficulty = difficulty.unwrap_or(self.settings.difficulty);
+                self.settings.mode = mode.unwrap_or(self.settings.mode);
+                self.app = App::new(&self.settings)?;
+                if let Some(build_ticks) = build_ticks {
+                    self.wave_settings.build_ticks = build_ticks;
+                }
+                if self.settings.mode == GameMode::Normal {
+                    self.app.set_wave_settings(self.wave_settings);
+                }
+                (self.score, self.hitpoints) = (self.app.score, self.app.hitpoints());
+            },
+            Command::Step { ticks: requested } => {
+                while ticks < requested && !self.app.is_over() {
+                    self.app.tick()?;
+                    ticks += 1;
+                }
+            },
+            Command::Act { action } => accepted = Some(self.app.act(action)),
+            Command::Observe | Command::Close => {}
+        }
+        let lost = self.hitpoints.saturating_sub(self.app.hitpoints());
+        let reward = self.app.score.saturating_sub(self.score) as f64 - lost as f64 * HITPOINT_PENALTY;
+        (self.score, self.hitpoints) = (self.app.score, self.app.hitpoints());
+        Ok(Response {
+            observation: self.app.observe(),
+            reward,
+            done: self.app.is_over(),
+            ticks,
+            accepted
+        })
+    }
+}
@@ -1,16 +1,30 @@
-use std::{
-    fs::File, io::{self, prelude::*}, path:: PathBuf
+use {
+    serde::{de::DeserializeOwned, Serialize},
+    std::{
+        fs::{self, File}, io::{self, prelude::*}, path:: PathBuf
+    }
 };
 
-pub fn save(path: &PathBuf, number: u64) -> io::Result<()> {
-    let mut file = File::create(path)?;
-    file.write_all(&number.to_le_bytes())?;
-    Ok(())
+// the old highscore file: one little endian u64 per difficulty, see Difficulty::storage_index
+pub fn read(path: &PathBuf) -> io::Result<Vec<u64>> {
+    let mut file = File::open(path)?;
+    let mut buffer = vec![];
+    file.read_to_end(&mut buffer)?;
+    Ok(buffer.chunks_exact(8).map(|chunk| {
+        u64::from_le_bytes(chunk.try_into().unwrap())
+    }).collect())
 }
 
-pub fn read(path: &PathBuf) -> io::Result<u64> {
-    let mut file = File::open(path)?;
-    let mut buffer = [0u8; 8];
-    file.read_exact(&mut buffer)?;
-    Ok(u64::from_le_bytes(buffer))
-}
\ No newline at end of file
+// writes to a temporary file first so a crash halfway through never leaves a truncated file behind
+pub fn save_json<T: Serialize>(path: &PathBuf, value: &T) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut file = File::create(&tmp)?;
+    serde_json::to_writer_pretty(&mut file, value)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+pub fn read_json<T: DeserializeOwned>(path: &PathBuf) -> io::Result<T> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(io::BufReader::new(file))?)
+}
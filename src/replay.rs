@@ -0,0 +1,47 @@
+use {
+    crate::{action::Action, app::GameSettings, read_write},
+    serde::{Deserialize, Serialize},
+    std::{io, path::PathBuf}
+};
+
+pub const REPLAY_FILE: &str = "last_replay.json";
+pub const SAVE_FILE: &str = "savegame.json";
+
+// the settings plus every action with the tick it was applied at reproduce a game exactly
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub settings: GameSettings,
+    pub actions: Vec<(u64, Action)>
+}
+
+impl Replay {
+    // the map comes with the file, so it gets the same check as one from the maps directory
+    pub fn load(path: &PathBuf) -> io::Result<Self> {
+        let replay: Replay = read_write::read_json(path)?;
+        replay.settings.map.check()?;
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &PathBuf) -> io::Result<()> {
+        read_write::save_json(path, self)
+    }
+}
+
+// a game is saved as the replay up to the moment of saving, continuing plays it back up to that tick
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub replay: Replay,
+    pub ticks: u64
+}
+
+impl SaveGame {
+    pub fn load(path: &PathBuf) -> io::Result<Self> {
+        let save: SaveGame = read_write::read_json(path)?;
+        save.replay.settings.map.check()?;
+        Ok(save)
+    }
+
+    pub fn save(&self, path: &PathBuf) -> io::Result<()> {
+        read_write::save_json(path, self)
+    }
+}
@@ -0,0 +1,92 @@
+use {
+    crate::{
+        app::WaveSettings,
+        theme::{self, Palette, Theme}
+    },
+    serde::{Deserialize, Serialize}
+};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+pub const MAPS_DIR: &str = "maps";
+
+const BUILD_TICK_CHOICES: [u32; 5] = [0, 5000, 10000, 20000, 40000];
+
+// what the player picked in the settings screen, kept in the profile directory
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub auto_start: bool,
+    pub build_ticks: u32,
+    pub speed: u8,
+    pub health_bars: bool,
+    pub damage_numbers: bool,
+    pub palette: Palette,
+    pub basic_colors: bool // only the 16 colors every terminal has
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let wave_settings = WaveSettings::default();
+        Self {
+            auto_start: wave_settings.auto_start,
+            build_ticks: wave_settings.build_ticks,
+            speed: 1,
+            health_bars: true,
+            damage_numbers: true,
+            palette: Palette::default(),
+            basic_colors: theme::basic_terminal()
+        }
+    }
+}
+
+impl Settings {
+    pub const ROWS: usize = 7;
+
+    pub fn wave_settings(&self) -> WaveSettings {
+        WaveSettings {
+            auto_start: self.auto_start,
+            build_ticks: self.build_ticks,
+            ..WaveSettings::default()
+        }
+    }
+
+    pub fn theme(&self) -> Theme {
+        Theme::new(self.palette, self.basic_colors)
+    }
+
+    // one row of the settings screen as label and value
+    pub fn row(&self, index: usize) -> (&'static str, String) {
+        match index {
+            0 => ("start waves automatically", on_off(self.auto_start)),
+            1 => ("time between waves", format!("{}s", self.build_ticks / 1000)),
+            2 => ("starting speed", format!("{}x", self.speed)),
+            3 => ("health bars", on_off(self.health_bars)),
+            4 => ("damage numbers", on_off(self.damage_numbers)),
+            5 => ("colors", self.palette.name().to_string()),
+            _ => ("16 colors only", on_off(self.basic_colors))
+        }
+    }
+
+    pub fn change(&mut self, index: usize, forward: bool) {
+        match index {
+            0 => self.auto_start = !self.auto_start,
+            1 => self.build_ticks = cycle(&BUILD_TICK_CHOICES, self.build_ticks, forward),
+            2 => self.speed = cycle(&[1, 2, 3], self.speed, forward),
+            3 => self.health_bars = !self.health_bars,
+            4 => self.damage_numbers = !self.damage_numbers,
+            5 => self.palette = cycle(&Palette::ALL, self.palette, forward),
+            _ => self.basic_colors = !self.basic_colors
+        }
+    }
+}
+
+fn on_off(enabled: bool) -> String {
+    if enabled { "on" } else { "off" }.to_string()
+}
+
+// the next or previous choice, wrapping around at both ends
+pub fn cycle<T: Copy + PartialEq>(choices: &[T], current: T, forward: bool) -> T {
+    let index = choices.iter().position(|choice| *choice == current).unwrap_or(0);
+    let next = if forward { (index + 1) % choices.len() } else { (index + choices.len() - 1) % choices.len() };
+    choices[next]
+}
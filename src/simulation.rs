@@ -0,0 +1,148 @@
+use {
+    crate::{
+        action::Action,
+        app::{App, GameSettings, WaveSettings},
+        towers::{TierStatus, Tower, TowerKind}
+    },
+    color_eyre::Result,
+    serde::{Deserialize, Serialize},
+    std::collections::VecDeque
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedTower {
+    pub kind: TowerKind,
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub upgrades: Vec<usize> // upgrade paths in the order they are bought
+}
+
+// towers are built in the order of the layout as soon as there is enough gold
+pub type Layout = Vec<PlannedTower>;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WaveResult {
+    pub wave: usize,
+    pub leaks: u64,
+    pub lives_lost: u16,
+    pub gold: u16 // at the end of the wave
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunResult {
+    pub seed: u64,
+    pub waves_survived: usize,
+    pub lives_lost: u16,
+    pub score: u64,
+    pub died_in_wave: Option<usize>,
+    pub won: bool, // survived the final wave of the map
+    pub skipped_steps: usize, // layout steps or bot actions the game refused
+    pub waves: Vec<WaveResult>
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    Place(usize),
+    Upgrade(usize, usize)
+}
+
+pub fn simulate(settings: &GameSettings, layout: &Layout, max_waves: usize, build_ticks: u32) -> Result<RunResult> {
+    let mut steps: VecDeque<Step> = layout.iter().enumerate().flat_map(|(i, planned)| {
+        std::iter::once(Step::Place(i)).chain(planned.upgrades.iter().map(move |path| Step::Upgrade(i, *path)))
+    }).collect();
+    let mut placed: Vec<Option<usize>> = vec![None; layout.len()]; // index of the planned tower in the game
+    let mut skipped = 0;
+    let mut result = drive(settings, max_waves, build_ticks, |app| {
+        build(app, layout, &mut steps, &mut placed, &mut skipped);
+    })?;
+    result.skipped_steps = skipped;
+    Ok(result)
+}
+
+// plays until the player died or survived max_waves waves, `act` gets to change the game before every tick
+pub fn drive(settings: &GameSettings, max_waves: usize, build_ticks: u32, mut act: impl FnMut(&mut App)) -> Result<RunResult> {
+    let mut app = App::new(settings)?;
+    app.set_wave_settings(WaveSettings {
+        build_ticks,
+        auto_start: true,
+        ..WaveSettings::default()
+    });
+    let mut result = RunResult {
+        seed: settings.seed,
+        ..RunResult::default()
+    };
+    let starting_hitpoints = app.hitpoints();
+    let (mut hitpoints, mut leaked) = (app.hitpoints(), 0);
+    let mut round = app.round;
+
+    loop {
+        act(&mut app);
+        if app.is_over() && !app.won() {
+            result.died_in_wave = Some(app.round);
+            break;
+        }
+        let wave_over = app.round > round || (app.building() && app.balloons_left() == 0);
+        if wave_over && round >= settings.start_wave && result.waves.last().is_none_or(|wave| wave.wave < round) {
+            result.waves.push(WaveResult {
+                wave: round,
+                leaks: app.leaked() - leaked,
+                lives_lost: hitpoints - app.hitpoints(),
+                gold: app.gold()
+            });
+            (hitpoints, leaked) = (app.hitpoints(), app.leaked());
+            if result.waves.len() >= max_waves {
+                break;
+            }
+        }
+        if app.won() { // the waves go on in freeplay up to max_waves
+            result.won = true;
+            app.act(Action::Freeplay);
+        }
+        round = app.round;
+        app.tick()?;
+    }
+    result.waves_survived = result.waves.len();
+    result.lives_lost = starting_hitpoints - app.hitpoints();
+    result.score = app.score;
+    Ok(result)
+}
+
+fn build(app: &mut App, layout: &Layout, steps: &mut VecDeque<Step>, placed: &mut [Option<usize>], skipped: &mut usize) {
+    while let Some(step) = steps.front().copied() {
+        let action = match step {
+            Step::Place(i) => Some(Action::PlaceTower { kind: layout[i].kind, x: layout[i].x, y: layout[i].y }),
+            Step::Upgrade(i, path) => placed[i].map(|tower| Action::Upgrade { tower, path })
+        };
+        let Some(action) = action else { // the tower this upgrade belongs to was never built
+            steps.pop_front();
+            *skipped += 1;
+            continue;
+        };
+        if app.act(action) {
+            if let Step::Place(i) = step {
+                placed[i] = Some(app.towers().len() - 1);
+            }
+            steps.pop_front();
+        }
+        else if blocked(app, &action) {
+            steps.pop_front();
+            *skipped += 1;
+        }
+        else { // not enough gold yet
+            break;
+        }
+    }
+}
+
+fn blocked(app: &App, action: &Action) -> bool {
+    match *action {
+        Action::PlaceTower { kind, x, y } => !app.placement_valid(&Tower::new(kind, x, y)),
+        Action::Upgrade { tower, path } => app.towers().get(tower).is_none_or(|tower| {
+            tower.upgrades.paths.get(path).is_none_or(|upgrade_path| {
+                tower.upgrades.tier_status(path, upgrade_path.level) != TierStatus::Available
+            })
+        }),
+        _ => false
+    }
+}
@@ -0,0 +1,174 @@
+use {
+    crate::{
+        balloons::BalloonKind,
+        events::{GameEvent, Subscriber},
+        read_write
+    },
+    color_eyre::{eyre::eyre, Result},
+    serde::Serialize,
+    std::{collections::BTreeMap, fmt::Write as _, fs, path::PathBuf}
+};
+
+pub type BalloonCounts = BTreeMap<BalloonKind, u64>;
+
+// everything that happened from the end of the previous wave until the end of this one
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WaveTelemetry {
+    pub wave: usize,
+    pub build_ticks: u64, // before the wave was started
+    pub ticks: u64, // from the start of the wave until the last balloon was gone
+    pub gold_earned: u64,
+    pub gold_spent: u64,
+    pub lives_lost: u64,
+    pub spawned: BalloonCounts,
+    pub popped: BalloonCounts,
+    pub leaked: BalloonCounts,
+    pub towers_built: u64,
+    pub towers_upgraded: u64,
+    pub towers_sold: u64,
+    #[serde(skip)]
+    opened_at: u64,
+    #[serde(skip)]
+    started_at: Option<u64>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Telemetry {
+    waves: Vec<WaveTelemetry> // the last one is the wave that is being played or built for
+}
+
+impl Telemetry {
+    pub fn new(first_wave: usize) -> Self {
+        Self {
+            waves: vec![WaveTelemetry {
+                wave: first_wave,
+                ..WaveTelemetry::default()
+            }]
+        }
+    }
+
+    fn current(&mut self) -> &mut WaveTelemetry {
+        if self.waves.is_empty() {
+            self.waves.push(WaveTelemetry::default());
+        }
+        self.waves.last_mut().unwrap()
+    }
+
+    fn start_wave(&mut self, wave: usize, tick: u64) {
+        let current = self.current();
+        if current.started_at.is_some() { // sandbox waves can be sent while another one is running
+            current.ticks = tick - current.started_at.unwrap_or(tick);
+            self.waves.push(WaveTelemetry {
+                wave,
+                opened_at: tick,
+                ..WaveTelemetry::default()
+            });
+        }
+        let current = self.current();
+        current.wave = wave;
+        current.build_ticks = tick - current.opened_at;
+        current.started_at = Some(tick);
+    }
+
+    fn end_wave(&mut self, tick: u64) {
+        let current = self.current();
+        current.ticks = tick - current.started_at.unwrap_or(tick);
+        let wave = current.wave + 1;
+        self.waves.push(WaveTelemetry {
+            wave,
+            opened_at: tick,
+            ..WaveTelemetry::default()
+        });
+    }
+
+    fn spawned(&mut self, kind: BalloonKind) {
+        *self.current().spawned.entry(kind).or_default() += 1;
+    }
+
+    fn popped(&mut self, kind: BalloonKind, gold: u16) {
+        let current = self.current();
+        *current.popped.entry(kind).or_default() += 1;
+        current.gold_earned += gold as u64;
+    }
+
+    fn leaked(&mut self, kind: BalloonKind, lives: u16) {
+        let current = self.current();
+        *current.leaked.entry(kind).or_default() += 1;
+        current.lives_lost += lives as u64;
+    }
+
+    // the waves so far, a wave that is still running is cut off at `tick`
+    pub fn waves(&self, tick: u64) -> Vec<WaveTelemetry> {
+        let mut waves = self.waves.clone();
+        if let Some(last) = waves.last_mut() {
+            match last.started_at {
+                Some(started_at) if last.ticks == 0 => last.ticks = tick - started_at,
+                None => last.build_ticks = tick - last.opened_at,
+                _ => {}
+            }
+        }
+        waves.retain(|wave| wave.started_at.is_some() || wave.gold_spent > 0 || wave.towers_sold > 0);
+        waves
+    }
+
+    // the format is picked by the extension of the file, .csv or .json
+    pub fn export(&self, path: &PathBuf, tick: u64) -> Result<()> {
+        let waves = self.waves(tick);
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => read_write::save_json(path, &waves)?,
+            Some("csv") => fs::write(path, csv(&waves)?)?,
+            _ => return Err(eyre!("unknown telemetry format {}, use .csv or .json", path.display()))
+        }
+        Ok(())
+    }
+}
+
+// everything is counted from the events, so the statistics can't miss what the message log and achievements see
+impl Subscriber for Telemetry {
+    fn notify(&mut self, tick: u64, event: &GameEvent) {
+        match *event {
+            GameEvent::WaveStarted { wave } => self.start_wave(wave, tick),
+            GameEvent::WaveCleared { .. } => self.end_wave(tick),
+            GameEvent::BalloonSpawned { kind } => self.spawned(kind),
+            GameEvent::BalloonPopped { kind, gold, .. } => self.popped(kind, gold),
+            GameEvent::BalloonLeaked { kind, lives } => self.leaked(kind, lives),
+            GameEvent::TowerBuilt { cost, .. } => {
+                let current = self.current();
+                current.towers_built += 1;
+                current.gold_spent += cost as u64;
+            },
+            GameEvent::TowerUpgraded { cost, .. } => {
+                let current = self.current();
+                current.towers_upgraded += 1;
+                current.gold_spent += cost as u64;
+            },
+            GameEvent::TowerSold { refund, .. } => {
+                let current = self.current();
+                current.towers_sold += 1;
+                current.gold_earned += refund as u64;
+            },
+            GameEvent::EarlyBonus { gold } => self.current().gold_earned += gold as u64,
+            _ => {}
+        }
+    }
+}
+
+fn csv(waves: &[WaveTelemetry]) -> Result<String> {
+    let mut csv = String::from("wave,build_ticks,ticks,gold_earned,gold_spent,lives_lost,towers_built,towers_upgraded,towers_sold");
+    for column in ["spawned", "popped", "leaked"] {
+        for kind in BalloonKind::ALL {
+            write!(csv, ",{}_{}", column, kind.name())?;
+        }
+    }
+    writeln!(csv)?;
+    for wave in waves {
+        write!(csv, "{},{},{},{},{},{},{},{},{}", wave.wave, wave.build_ticks, wave.ticks, wave.gold_earned, wave.gold_spent, wave.lives_lost, wave.towers_built, wave.towers_upgraded, wave.towers_sold)?;
+        for counts in [&wave.spawned, &wave.popped, &wave.leaked] {
+            for kind in BalloonKind::ALL {
+                write!(csv, ",{}", counts.get(&kind).copied().unwrap_or(0))?;
+            }
+        }
+        writeln!(csv)?;
+    }
+    Ok(csv)
+}
@@ -0,0 +1,317 @@
+use {
+    crate::balloons::BalloonKind,
+    ratatui::style::Color,
+    serde::{Deserialize, Serialize}
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Palette {
+    #[default]
+    Classic,
+    Colorblind, // the Okabe-Ito colors, told apart with every kind of color blindness
+    HighContrast
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::Classic, Palette::Colorblind, Palette::HighContrast];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Classic => "classic",
+            Palette::Colorblind => "colorblind",
+            Palette::HighContrast => "high contrast"
+        }
+    }
+}
+
+// what a tower or projectile is, the theme decides which color that is, balloons are colored by their kind
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Paint {
+    #[default]
+    DartTower,
+    SteelDartTower,
+    TripleDartTower,
+    FireTower,
+    InfernoTower,
+    Dart,
+    RazorDart,
+    SteelDart,
+    Flame,
+    Fireball
+}
+
+// one color per Paint, named so that a palette can't mix them up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Paints {
+    dart_tower: Color,
+    steel_dart_tower: Color,
+    triple_dart_tower: Color,
+    fire_tower: Color,
+    inferno_tower: Color,
+    dart: Color,
+    razor_dart: Color,
+    steel_dart: Color,
+    flame: Color,
+    fireball: Color
+}
+
+impl Paints {
+    fn map(self, f: fn(Color) -> Color) -> Self {
+        Self {
+            dart_tower: f(self.dart_tower),
+            steel_dart_tower: f(self.steel_dart_tower),
+            triple_dart_tower: f(self.triple_dart_tower),
+            fire_tower: f(self.fire_tower),
+            inferno_tower: f(self.inferno_tower),
+            dart: f(self.dart),
+            razor_dart: f(self.razor_dart),
+            steel_dart: f(self.steel_dart),
+            flame: f(self.flame),
+            fireball: f(self.fireball)
+        }
+    }
+}
+
+// one color per BalloonKind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BalloonColors {
+    red: Color,
+    blue: Color,
+    blimp: Color
+}
+
+impl BalloonColors {
+    fn get(&self, kind: BalloonKind) -> Color {
+        match kind {
+            BalloonKind::Red => self.red,
+            BalloonKind::Blue => self.blue,
+            BalloonKind::Blimp => self.blimp
+        }
+    }
+
+    fn map(self, f: fn(Color) -> Color) -> Self {
+        Self {
+            red: f(self.red),
+            blue: f(self.blue),
+            blimp: f(self.blimp)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub background: Color,
+    pub text: Color,
+    pub path: Color,
+    pub highlight: Color, // selected entries and things that want attention
+    pub muted: Color, // what can't be used right now
+    pub good: Color,
+    pub bad: Color,
+    pub cursor: Color,
+    pub range: Color,
+    pub damage: Color,
+    pub pop: Color,
+    pub health: Color,
+    pub worn: Color, // balloons with less than a third of their hitpoints left
+    paints: Paints,
+    balloons: BalloonColors,
+    hurt: BalloonColors // balloons with less than two thirds of their hitpoints left
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(Palette::default(), false)
+    }
+}
+
+impl Theme {
+    // with `basic` every color is one of the 16 that any terminal can show
+    pub fn new(palette: Palette, basic: bool) -> Self {
+        let theme = match palette {
+            Palette::Classic => Self::classic(),
+            Palette::Colorblind => Self::colorblind(),
+            Palette::HighContrast => Self::high_contrast()
+        };
+        if basic {
+            theme.basic()
+        }
+        else {
+            theme
+        }
+    }
+
+    pub fn paint(&self, paint: Paint) -> Color {
+        match paint {
+            Paint::DartTower => self.paints.dart_tower,
+            Paint::SteelDartTower => self.paints.steel_dart_tower,
+            Paint::TripleDartTower => self.paints.triple_dart_tower,
+            Paint::FireTower => self.paints.fire_tower,
+            Paint::InfernoTower => self.paints.inferno_tower,
+            Paint::Dart => self.paints.dart,
+            Paint::RazorDart => self.paints.razor_dart,
+            Paint::SteelDart => self.paints.steel_dart,
+            Paint::Flame => self.paints.flame,
+            Paint::Fireball => self.paints.fireball
+        }
+    }
+
+    // balloons fade as they lose hitpoints
+    pub fn balloon(&self, kind: BalloonKind, health: f64) -> Color {
+        if health > 2.0 / 3.0 {
+            return self.balloons.get(kind);
+        }
+        if health <= 1.0 / 3.0 {
+            return self.worn;
+        }
+        self.hurt.get(kind)
+    }
+
+    fn classic() -> Self {
+        Self {
+            background: Color::Black,
+            text: Color::Reset,
+            path: Color::White,
+            highlight: Color::Yellow,
+            muted: Color::DarkGray,
+            good: Color::Green,
+            bad: Color::Red,
+            cursor: Color::Yellow,
+            range: Color::DarkGray,
+            damage: Color::White,
+            pop: Color::Yellow,
+            health: Color::Green,
+            worn: Color::Gray,
+            paints: Paints {
+                dart_tower: Color::Blue,
+                steel_dart_tower: Color::Cyan,
+                triple_dart_tower: Color::LightBlue,
+                fire_tower: Color::LightRed,
+                inferno_tower: Color::Red,
+                dart: Color::Gray,
+                razor_dart: Color::White,
+                steel_dart: Color::LightCyan,
+                flame: Color::Yellow,
+                fireball: Color::LightRed
+            },
+            balloons: BalloonColors { red: Color::Red, blue: Color::Blue, blimp: Color::Magenta },
+            hurt: BalloonColors { red: Color::LightRed, blue: Color::LightBlue, blimp: Color::LightMagenta }
+        }
+    }
+
+    fn colorblind() -> Self {
+        let orange = Color::Rgb(230, 159, 0);
+        let sky_blue = Color::Rgb(86, 180, 233);
+        let green = Color::Rgb(0, 158, 115);
+        let yellow = Color::Rgb(240, 228, 66);
+        let blue = Color::Rgb(0, 114, 178);
+        let vermillion = Color::Rgb(213, 94, 0);
+        let purple = Color::Rgb(204, 121, 167);
+        Self {
+            background: Color::Black,
+            text: Color::Reset,
+            path: Color::White,
+            highlight: yellow,
+            muted: Color::DarkGray,
+            good: sky_blue,
+            bad: vermillion,
+            cursor: yellow,
+            range: Color::DarkGray,
+            damage: Color::White,
+            pop: yellow,
+            health: sky_blue,
+            worn: Color::Gray,
+            paints: Paints {
+                dart_tower: sky_blue,
+                steel_dart_tower: green,
+                triple_dart_tower: blue,
+                fire_tower: orange,
+                inferno_tower: vermillion,
+                dart: Color::Gray,
+                razor_dart: Color::White,
+                steel_dart: sky_blue,
+                flame: yellow,
+                fireball: orange
+            },
+            balloons: BalloonColors { red: vermillion, blue, blimp: purple },
+            hurt: BalloonColors { red: orange, blue: sky_blue, blimp: Color::Rgb(230, 180, 210) }
+        }
+    }
+
+    fn high_contrast() -> Self {
+        Self {
+            background: Color::Black,
+            text: Color::White,
+            path: Color::White,
+            highlight: Color::LightYellow,
+            muted: Color::Gray,
+            good: Color::LightGreen,
+            bad: Color::LightRed,
+            cursor: Color::LightYellow,
+            range: Color::Gray,
+            damage: Color::White,
+            pop: Color::LightYellow,
+            health: Color::LightGreen,
+            worn: Color::White,
+            paints: Paints {
+                dart_tower: Color::LightCyan,
+                steel_dart_tower: Color::White,
+                triple_dart_tower: Color::LightBlue,
+                fire_tower: Color::LightYellow,
+                inferno_tower: Color::LightRed,
+                dart: Color::White,
+                razor_dart: Color::White,
+                steel_dart: Color::LightCyan,
+                flame: Color::LightYellow,
+                fireball: Color::LightRed
+            },
+            balloons: BalloonColors { red: Color::LightRed, blue: Color::LightCyan, blimp: Color::LightMagenta },
+            hurt: BalloonColors { red: Color::Red, blue: Color::Cyan, blimp: Color::Magenta }
+        }
+    }
+
+    fn basic(mut self) -> Self {
+        for color in [&mut self.background, &mut self.text, &mut self.path, &mut self.highlight, &mut self.muted, &mut self.good, &mut self.bad,
+            &mut self.cursor, &mut self.range, &mut self.damage, &mut self.pop, &mut self.health, &mut self.worn] {
+            *color = nearest_basic(*color);
+        }
+        self.paints = self.paints.map(nearest_basic);
+        self.balloons = self.balloons.map(nearest_basic);
+        self.hurt = self.hurt.map(nearest_basic);
+        self
+    }
+}
+
+// true color terminals say so in COLORTERM, everything else gets the 16 basic colors
+pub fn basic_terminal() -> bool {
+    !matches!(std::env::var("COLORTERM").as_deref(), Ok("truecolor") | Ok("24bit"))
+}
+
+// the closest of the 16 colors, using the usual xterm values for them
+fn nearest_basic(color: Color) -> Color {
+    const BASIC: [(Color, (i32, i32, i32)); 16] = [
+        (Color::Black, (0, 0, 0)),
+        (Color::Red, (205, 0, 0)),
+        (Color::Green, (0, 205, 0)),
+        (Color::Yellow, (205, 205, 0)),
+        (Color::Blue, (0, 0, 238)),
+        (Color::Magenta, (205, 0, 205)),
+        (Color::Cyan, (0, 205, 205)),
+        (Color::Gray, (229, 229, 229)),
+        (Color::DarkGray, (127, 127, 127)),
+        (Color::LightRed, (255, 0, 0)),
+        (Color::LightGreen, (0, 255, 0)),
+        (Color::LightYellow, (255, 255, 0)),
+        (Color::LightBlue, (92, 92, 255)),
+        (Color::LightMagenta, (255, 0, 255)),
+        (Color::LightCyan, (0, 255, 255)),
+        (Color::White, (255, 255, 255))
+    ];
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    let (r, g, b) = (r as i32, g as i32, b as i32);
+    BASIC.iter()
+        .min_by_key(|(_, (br, bg, bb))| (r - br).pow(2) + (g - bg).pow(2) + (b - bb).pow(2))
+        .map_or(color, |(basic, _)| *basic)
+}
@@ -2,80 +2,156 @@ use {
     crate::{
         app::BalloonPath,
         balloons::Balloon,
+        theme::{Paint, Theme},
         utils::*
     }, 
     color_eyre::Result, 
+    serde::{Deserialize, Serialize},
     ratatui::{
-        prelude::Color, 
+        prelude::{Color, Stylize}, 
         text, 
         widgets::canvas::{
             Circle,
             Context,
-            Points,
             Rectangle
         }
     }
 };
 
+// what a row of the selected tower panel does when it is clicked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelRow {
+    Path(usize),
+    Sell
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TowerStats {
+    pub pops: u64,
+    pub damage: f64,
+    pub shots: u64,
+    pub misses: u64,
+    pub gold: u64
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TowerKind {
+    #[default]
+    DartThrower,
+    FlameThrower
+}
+
+impl TowerKind {
+    pub const ALL: [TowerKind; 2] = [TowerKind::DartThrower, TowerKind::FlameThrower];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TowerKind::DartThrower => "dart thrower",
+            TowerKind::FlameThrower => "flame thrower"
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Tower {
+    pub kind: TowerKind,
+    pub name: &'static str,
     pub x: f64,
     pub y: f64,
     pub height: f64,
     pub width: f64,
-    pub color: Color,
+    pub paint: Paint,
     pub projectiles: Vec<Projectile>,
     pub cost: u16,
+    pub spent_on_upgrades: u16,
     pub upgrades: TowerUpgradeShop,
+    pub stats: TowerStats,
     damage_per_projectile: f64,
     projectile_speed: f64,
     ticks_per_projectile: u16,
     ticks_since_last_projectile: u16,
     range: f64,
-    projectile_color: Color,
-    projectile_size: f64,
+    projectile_kind: ProjectileKind,
 }
 
 //find out how to do inheritance in rust (traits, ...)
 impl Tower{
 
+    pub fn new(kind: TowerKind, x: f64, y: f64) -> Self {
+        match kind {
+            TowerKind::DartThrower => Self::dart_thrower(x, y),
+            TowerKind::FlameThrower => Self::flame_thrower(x, y)
+        }
+    }
+
     pub fn dart_thrower(x: f64, y: f64) -> Self {
         Self {
+            kind: TowerKind::DartThrower,
+            name: TowerKind::DartThrower.name(),
             x: x,
             y: y,
             height: 5.0,
             width: 5.0, 
-            color: Color::Blue,
+            paint: Paint::DartTower,
             projectiles: vec![],
             damage_per_projectile: 10.0,
             cost: 10,
+            spent_on_upgrades: 0,
+            stats: TowerStats::default(),
             projectile_speed: 0.5,
             ticks_per_projectile: 800,
             ticks_since_last_projectile: 0,
             range: 90.0,
-            projectile_color: Color::Gray,
-            projectile_size: 1.0,
-            upgrades: TowerUpgradeShop::new(vec![Upgrade::RangeUpgrade(50, 20.0), Upgrade::DamageUpgrade(40, 5.0), Upgrade::FireRateUpgrade(30, 20), Upgrade::ProjectileSpeedUpgrade(10, 10.0)]),
+            projectile_kind: ProjectileKind::Dart,
+            upgrades: TowerUpgradeShop::new(vec![
+                UpgradePath::new("sharp darts", vec![
+                    UpgradeTier::new("sharp tips", 40, vec![Upgrade::DamageUpgrade(5.0)]),
+                    UpgradeTier::new("razor darts", 80, vec![Upgrade::DamageUpgrade(10.0), Upgrade::ProjectileUpgrade(ProjectileKind::RazorDart)]),
+                    UpgradeTier::new("steel darts", 150, vec![Upgrade::DamageUpgrade(20.0), Upgrade::ProjectileUpgrade(ProjectileKind::SteelDart), Upgrade::AppearanceUpgrade(Paint::SteelDartTower)])
+                ]).excludes(vec![2]),
+                UpgradePath::new("long reach", vec![
+                    UpgradeTier::new("long arm", 50, vec![Upgrade::RangeUpgrade(20.0)]),
+                    UpgradeTier::new("eagle eye", 70, vec![Upgrade::RangeUpgrade(20.0), Upgrade::ProjectileSpeedUpgrade(0.25)]),
+                    UpgradeTier::new("sniper", 120, vec![Upgrade::RangeUpgrade(40.0), Upgrade::ProjectileSpeedUpgrade(0.5)])
+                ]).cap(2),
+                UpgradePath::new("rapid fire", vec![
+                    UpgradeTier::new("quick hands", 30, vec![Upgrade::FireRateUpgrade(200)]),
+                    UpgradeTier::new("triple throw", 90, vec![Upgrade::FireRateUpgrade(300), Upgrade::AppearanceUpgrade(Paint::TripleDartTower)]).requires(1, 1)
+                ]).excludes(vec![0])
+            ]),
         }
     }
 
     pub fn flame_thrower(x: f64, y: f64) -> Self {
         Self {
+            kind: TowerKind::FlameThrower,
+            name: TowerKind::FlameThrower.name(),
             x: x,  
             y: y,
             height: 5.0,
             width: 5.0, 
-            color: Color::LightRed,
+            paint: Paint::FireTower,
             projectiles: vec![],
             damage_per_projectile: 0.01,
             cost: 30,
+            spent_on_upgrades: 0,
+            stats: TowerStats::default(),
             projectile_speed: 0.3,
             ticks_per_projectile: 0,
             ticks_since_last_projectile: 0,
             range: 45.0,
-            projectile_color: Color::Yellow,
-            projectile_size: 1.5,
-            upgrades: TowerUpgradeShop::new(vec![Upgrade::RangeUpgrade(50, 20.0), Upgrade::DamageUpgrade(40, 5.0)]),
+            projectile_kind: ProjectileKind::Flame,
+            upgrades: TowerUpgradeShop::new(vec![
+                UpgradePath::new("hotter flames", vec![
+                    UpgradeTier::new("hot flames", 40, vec![Upgrade::DamageUpgrade(0.01)]),
+                    UpgradeTier::new("inferno", 100, vec![Upgrade::DamageUpgrade(0.02), Upgrade::ProjectileUpgrade(ProjectileKind::Fireball), Upgrade::AppearanceUpgrade(Paint::InfernoTower)])
+                ]),
+                UpgradePath::new("wide nozzle", vec![
+                    UpgradeTier::new("wide nozzle", 50, vec![Upgrade::RangeUpgrade(15.0)]),
+                    UpgradeTier::new("long nozzle", 80, vec![Upgrade::RangeUpgrade(15.0)]).requires(0, 1)
+                ])
+            ]),
         }
     }
 
@@ -100,11 +176,12 @@ impl Tower{
         let mut new_projectile = Projectile {
             x: self.x,
             y: self.y + self.height / 2.0,
-            radius: self.projectile_size,    //num::ToPrimitive, 
-            color: self.projectile_color,
+            radius: self.projectile_kind.size(),
+            paint: self.projectile_kind.paint(),
             trajectory: vec![],
             flying_time: 0,
-            target_ballon: Option::from(index)
+            target_ballon: Option::from(index),
+            hit: false
         };
 
         //TODO: replace this shitty recursive function with a trigonometric calculation --> triangle : self, ballon current, ballon in t + flight time to current -> problem: nonlinear path
@@ -112,6 +189,7 @@ impl Tower{
 
         if target_set {
             self.projectiles.push(new_projectile);
+            self.stats.shots += 1;
         }
         Ok(true)
     }
@@ -120,6 +198,9 @@ impl Tower{
         let mut k = 0;
         for i in 0..self.projectiles.len() {
             if self.projectiles[i - k].flying_time < 0 {
+                if !self.projectiles[i - k].hit { // the target was popped before the projectile arrived
+                    self.stats.misses += 1;
+                }
                 self.projectiles.remove(i - k);
                 k += 1;
                 continue;
@@ -173,6 +254,15 @@ impl Tower{
         dmg
     }
 
+    // the projectiles that just landed on the balloon, so that they don't count as misses after its target is gone
+    pub fn register_hits(&mut self, index: usize) {
+        for projectile in self.projectiles.iter_mut() {
+            if projectile.flying_time == 0 && projectile.target_ballon == Some(index) {
+                projectile.hit = true;
+            }
+        }
+    }
+
     pub fn remove_target_of_projectile(&mut self, index: usize) -> Result<()> {
         for projectile in self.projectiles.iter_mut() {
             match projectile.target_ballon {
@@ -187,56 +277,97 @@ impl Tower{
         Ok(())
     }
 
-    pub fn render_self(&self, ctx: &mut Context) {
+    pub fn render_self(&self, ctx: &mut Context, theme: &Theme) {
         ctx.draw(&Rectangle {
             x: self.x,
             y: self.y,
             width: self.width,
             height: self.height,
-            color: self.color
+            color: theme.paint(self.paint)
         });
     }
 
+    // a tower that is still being placed, green where it can be built and red where it can't
+    pub fn render_ghost(&self, ctx: &mut Context, theme: &Theme, valid: bool) {
+        let color = if valid { theme.good } else { theme.bad };
+        self.render_range(ctx, color);
+        ctx.draw(&Rectangle {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            color
+        });
+    }
+
+    // centered on the point projectiles are thrown from
+    pub fn render_range(&self, ctx: &mut Context, color: Color) {
+        ctx.draw(&Circle {
+            x: self.x,
+            y: self.y + self.height / 2.0,
+            radius: self.range,
+            color
+        });
+    }
+
+    // the lines of the selected tower panel, each with what clicking it does
+    pub fn panel(&self, refund: f64, theme: &Theme, sell_key: &str) -> Vec<(text::Line<'static>, Option<PanelRow>)> {
+        let mut lines = self.upgrades.lines(theme);
+        let stats = &self.stats;
+        lines.push((text::Line::from(vec!["pops ".into(), text::Span::from(stats.pops.to_string()), " dmg ".into(), text::Span::from(format!("{:.0}", stats.damage))]), None));
+        lines.push((text::Line::from(vec!["shots ".into(), text::Span::from(stats.shots.to_string()), " miss ".into(), text::Span::from(stats.misses.to_string())]), None));
+        lines.push((text::Line::from(vec!["gold ".into(), text::Span::from(stats.gold.to_string()), " $".into()]), None));
+        lines.push((text::Line::from(""), None));
+        lines.push((text::Line::from(vec![text::Span::from(format!("sell {sell_key} ")), text::Span::from(self.sell_value(refund).to_string()), " $".into()]).fg(theme.bad), Some(PanelRow::Sell)));
+        lines
+    }
+
     pub fn show_upgrades(&mut self) {
         self.upgrades.show_upgrades = !self.upgrades.show_upgrades;
     }
 
-    pub fn buy_upgrade(&mut self, y: f64, gold: &u16) -> Option<u16> {
-        if let Some(upgrade) = self.upgrades.upgrade(y) {
-            match upgrade {
-                Upgrade::DamageUpgrade(cost, dmg) => {
-                    if *gold >= cost {
-                        self.damage_per_projectile += dmg;
-                        return Some(cost);
-                    }
-                },
-                Upgrade::FireRateUpgrade(cost, rate) => {
-                    if *gold >= cost {
-                        if self.ticks_per_projectile >= rate {
-                            self.ticks_per_projectile -= rate;
-                            return Some(cost);
-                        }
-                    }
-                },
-                Upgrade::RangeUpgrade(cost, range) => {
-                    if *gold >= cost {
-                        if self.range < 180.0 {
-                            self.range += range;
-                            return Some(cost);
-                        }
-                    }
-                },
-                Upgrade::ProjectileSpeedUpgrade(cost, value) => {
-                    if *gold >= cost {
-                        if self.projectile_speed < 180.0 {  
-                            self.projectile_speed += value;
-                            return Some(cost);
-                        }
-                    }
-                }
+    pub fn buy_upgrade(&mut self, path: usize, gold: &u16) -> Option<UpgradeTier> {
+        if path >= self.upgrades.paths.len() {
+            return None;
+        }
+        let tier = self.upgrades.next_tier(path)?.clone();
+        if *gold < tier.cost {
+            return None;
+        }
+        for effect in tier.effects.iter() {
+            self.apply_upgrade(effect);
+        }
+        self.upgrades.paths[path].level += 1;
+        self.spent_on_upgrades += tier.cost;
+        Some(tier)
+    }
+
+    pub fn scale_costs(&mut self, multiplier: f64) {
+        self.cost = (self.cost as f64 * multiplier).round() as u16;
+        for path in self.upgrades.paths.iter_mut() {
+            for tier in path.tiers.iter_mut() {
+                tier.cost = (tier.cost as f64 * multiplier).round() as u16;
             }
         }
-        None
+    }
+
+    pub fn range(&self) -> f64 {
+        self.range
+    }
+
+    pub fn sell_value(&self, refund: f64) -> u16 {
+        ((self.cost + self.spent_on_upgrades) as f64 * refund).floor() as u16
+    }
+
+    fn apply_upgrade(&mut self, upgrade: &Upgrade) {
+        match upgrade {
+            Upgrade::DamageUpgrade(dmg) => self.damage_per_projectile += dmg,
+            Upgrade::FireRateUpgrade(rate) => self.ticks_per_projectile = self.ticks_per_projectile.saturating_sub(*rate),
+            Upgrade::RangeUpgrade(range) => self.range = (self.range + range).min(180.0),
+            Upgrade::ProjectileSpeedUpgrade(value) => self.projectile_speed += value,
+            Upgrade::ProjectileUpgrade(kind) => self.projectile_kind = *kind,
+            Upgrade::AppearanceUpgrade(paint) => self.paint = *paint
+        }
     }
 
 }
@@ -246,45 +377,33 @@ impl Tower{
 pub struct TowerShop {
     pub n_towers: usize,
     towers: Vec<Tower>,
-    tower_names: Vec<&'static str>,
 }
 
 impl TowerShop {
-    pub fn new() -> Self {
-        let n = 2.0;
+    pub fn new(cost_multiplier: f64) -> Self {
+        let mut towers: Vec<Tower> = TowerKind::ALL.iter().map(|kind| Tower::new(*kind, 0.0, 0.0)).collect();
+        for tower in towers.iter_mut() {
+            tower.scale_costs(cost_multiplier);
+        }
         Self {
-            n_towers: n as usize,
-            towers: vec![
-                Tower::dart_thrower(180.0 / n / 2.0 - 90.0, -80.0), 
-                Tower::flame_thrower(1.0 * 180.0 / n + 180.0 / n / 2.0 - 90.0, -80.0)
-                ],
-            tower_names: vec!["dart thrower", "flame thrower"]
+            n_towers: towers.len(),
+            towers
         }
     }
     
-    pub fn render_self(&self, ctx: &mut Context) {
-        for i in 0..self.n_towers {
-            ctx.draw(&Rectangle {
-                x: (i as f64 * 180.0 / self.n_towers as f64) - 90.0,
-                y: -90.0,
-                width: 180.0 / self.n_towers as f64,
-                height: 20.0,
-                color: Color::White,
-            });
-            ctx.layer();
-            self.towers[i].render_self(ctx);
-            ctx.print(self.towers[i].x - 2.0, self.towers[i].y - 5.0, text::Line::from(self.tower_names[i]));
-            ctx.print(self.towers[i].x + 2.0, self.towers[i].y - 8.0, text::Line::from(vec![text::Span::from(self.towers[i].cost.to_string()), " $".into()]));
-        }
-    }
-
-    pub fn get_tower(&self, x: f64, gold: &u16) -> Option<Tower> {
-        for i in 0..self.towers.len() {
-            if x <= (i + 1) as f64 * 180.0 / self.n_towers as f64 - 90.0 {
-                if *gold >= self.towers[i].cost {
-                    return Option::from(self.towers[i].clone());
-                }
-            }
+    pub fn towers(&self) -> &[Tower] {
+        &self.towers
+    }
+
+    pub fn tower_of_kind(&self, kind: TowerKind, gold: &u16) -> Option<Tower> {
+        let index = TowerKind::ALL.iter().position(|k| *k == kind)?;
+        self.tower(index, gold)
+    }
+
+    pub fn tower(&self, index: usize, gold: &u16) -> Option<Tower> {
+        let tower = self.towers.get(index)?;
+        if *gold >= tower.cost {
+            return Some(tower.clone());
         }
         None
     }
@@ -295,10 +414,11 @@ pub struct Projectile {
     pub x: f64,
     pub y: f64,
     pub radius: f64,
-    pub color: Color,
+    pub paint: Paint,
     trajectory: Vec<f64>,
     pub flying_time: i64,
-    target_ballon: Option<usize>
+    target_ballon: Option<usize>,
+    hit: bool // whether it did damage when it landed
 }
 
 impl Projectile {
@@ -309,45 +429,180 @@ impl Projectile {
     }
 }
 
+
+#[derive (Debug, Default, Clone, Copy, PartialEq)]
+pub enum ProjectileKind {
+    #[default]
+    Dart,
+    RazorDart,
+    SteelDart,
+    Flame,
+    Fireball
+}
+
+impl ProjectileKind {
+    pub fn paint(&self) -> Paint {
+        match self {
+            ProjectileKind::Dart => Paint::Dart,
+            ProjectileKind::RazorDart => Paint::RazorDart,
+            ProjectileKind::SteelDart => Paint::SteelDart,
+            ProjectileKind::Flame => Paint::Flame,
+            ProjectileKind::Fireball => Paint::Fireball
+        }
+    }
+
+    pub fn size(&self) -> f64 {
+        match self {
+            ProjectileKind::Dart | ProjectileKind::RazorDart => 1.0,
+            ProjectileKind::SteelDart => 1.5,
+            ProjectileKind::Flame => 1.5,
+            ProjectileKind::Fireball => 2.5
+        }
+    }
+}
+
+#[derive (Debug, Clone, Copy, PartialEq)]
+pub enum TierStatus {
+    Purchased,
+    Available,
+    Locked
+}
+
+#[derive (Debug, Clone)]
+pub struct UpgradeTier {
+    pub name: &'static str,
+    pub cost: u16,
+    pub effects: Vec<Upgrade>,
+    requires: Vec<(usize, usize)> // (path, level) that has to be reached in another path
+}
+
+impl UpgradeTier {
+    fn new(name: &'static str, cost: u16, effects: Vec<Upgrade>) -> Self {
+        Self {
+            name,
+            cost,
+            effects,
+            requires: vec![]
+        }
+    }
+
+    fn requires(mut self, path: usize, level: usize) -> Self {
+        self.requires.push((path, level));
+        self
+    }
+}
+
+#[derive (Debug, Clone)]
+pub struct UpgradePath {
+    pub name: &'static str,
+    pub tiers: Vec<UpgradeTier>,
+    pub level: usize,
+    cap: usize,
+    excludes: Vec<usize> // paths that get locked as soon as this one is upgraded
+}
+
+impl UpgradePath {
+    fn new(name: &'static str, tiers: Vec<UpgradeTier>) -> Self {
+        let cap = tiers.len();
+        Self {
+            name,
+            tiers,
+            level: 0,
+            cap,
+            excludes: vec![]
+        }
+    }
+
+    // the tiers past the cap are only for towers that have no other upgrades
+    fn cap(mut self, cap: usize) -> Self {
+        self.cap = cap.min(self.tiers.len());
+        self
+    }
+
+    fn excludes(mut self, paths: Vec<usize>) -> Self {
+        self.excludes = paths;
+        self
+    }
+
+    pub fn maxed(&self) -> bool {
+        self.level >= self.tiers.len()
+    }
+}
+
 #[derive (Debug, Default, Clone)]
 pub struct TowerUpgradeShop {
     pub show_upgrades: bool,
-    possible_upgrades: Vec<Upgrade>
+    pub paths: Vec<UpgradePath>
 }
 
 impl TowerUpgradeShop {
-    pub fn render_self(&self, ctx: &mut Context, diff_to_180: f64) {
-        if !self.show_upgrades {
-            return;
-        }
-        let min = 180.0 + diff_to_180;
-        let d = 90.0 + diff_to_180;
-        for (i, upgrade) in self.possible_upgrades.iter().enumerate() {
-            ctx.draw(&Rectangle {
-                x: 70.0,
-                y: i as f64 * min / self.possible_upgrades.len() as f64 - d,
-                width: 20.0,
-                height: min / self.possible_upgrades.len() as f64,
-                color: Color::White
-            });
-            let x = 80.0;
-            let y = i as f64 * min / self.possible_upgrades.len() as f64 - d + min / self.possible_upgrades.len() as f64 / 2.0;
-            upgrade.render_self(ctx, x, y);
+    // a header and one line per tier for every path, followed by an empty line
+    fn lines(&self, theme: &Theme) -> Vec<(text::Line<'static>, Option<PanelRow>)> {
+        let mut lines = vec![];
+        for (i, path) in self.paths.iter().enumerate() {
+            let key = format!("<{}> ", i + 1);
+            let header = if self.path_locked(i) {
+                text::Line::from(vec![key.into(), text::Span::from(path.name), " (locked)".into()]).fg(theme.muted)
+            }
+            else {
+                text::Line::from(vec![key.into(), text::Span::from(path.name)]).fg(theme.highlight)
+            };
+            lines.push((header, Some(PanelRow::Path(i))));
+            for (level, tier) in path.tiers.iter().enumerate() {
+                let line = match self.tier_status(i, level) {
+                    TierStatus::Purchased => text::Line::from(vec!["  + ".into(), text::Span::from(tier.name)]).fg(theme.good),
+                    TierStatus::Available => text::Line::from(vec!["  ".into(), text::Span::from(tier.name), " ".into(), text::Span::from(tier.cost.to_string()), " $".into()]).fg(theme.text),
+                    TierStatus::Locked => text::Line::from(vec!["  ".into(), text::Span::from(tier.name), " ".into(), text::Span::from(tier.cost.to_string()), " $".into()]).fg(theme.muted)
+                };
+                lines.push((line, Some(PanelRow::Path(i))));
+            }
+            lines.push((text::Line::from(""), None));
         }
+        lines
     }
-    
-    fn new(upgrades: Vec<Upgrade>) -> Self {
+
+    fn new(paths: Vec<UpgradePath>) -> Self {
         Self {
             show_upgrades: false,
-            possible_upgrades: upgrades
+            paths
         }
     }
 
-    fn upgrade(&mut self, y: f64) -> Option<Upgrade> {
-        for (i, upgrade) in self.possible_upgrades.iter().enumerate() {
-            if y <= (i + 1) as f64 * 160.0 / self.possible_upgrades.len() as f64 - 70.0 {
-                return Some(upgrade.clone());
-            }
+    pub fn path_locked(&self, index: usize) -> bool {
+        self.paths.iter().enumerate().any(|(i, path)| {
+            i != index && path.level > 0 && (path.excludes.contains(&index) || self.paths[index].excludes.contains(&i) || path.level > path.cap)
+        })
+    }
+
+    // whether any path but this one has been upgraded
+    fn mixed(&self, index: usize) -> bool {
+        self.paths.iter().enumerate().any(|(i, path)| i != index && path.level > 0)
+    }
+
+    pub fn tier_status(&self, path: usize, level: usize) -> TierStatus {
+        let upgrade_path = &self.paths[path];
+        if level < upgrade_path.level {
+            return TierStatus::Purchased;
+        }
+        if level > upgrade_path.level || upgrade_path.maxed() || self.path_locked(path) || (level >= upgrade_path.cap && self.mixed(path)) {
+            return TierStatus::Locked;
+        }
+        let requirements_met = upgrade_path.tiers[level].requires.iter().all(|(other, needed)| {
+            self.paths[*other].level >= *needed
+        });
+        if requirements_met {
+            TierStatus::Available
+        }
+        else {
+            TierStatus::Locked
+        }
+    }
+
+    // returns the next tier of the given path if it can be bought right now
+    pub fn next_tier(&self, path: usize) -> Option<&UpgradeTier> {
+        let level = self.paths[path].level;
+        if self.tier_status(path, level) == TierStatus::Available {
+            return self.paths[path].tiers.get(level);
         }
         None
     }
@@ -355,53 +610,10 @@ impl TowerUpgradeShop {
 
 #[derive (Debug, Clone)]
 pub enum Upgrade {
-    RangeUpgrade(u16, f64),
-    DamageUpgrade(u16, f64),
-    FireRateUpgrade(u16, u16),
-    ProjectileSpeedUpgrade(u16, f64)
+    RangeUpgrade(f64),
+    DamageUpgrade(f64),
+    FireRateUpgrade(u16),
+    ProjectileSpeedUpgrade(f64),
+    ProjectileUpgrade(ProjectileKind),
+    AppearanceUpgrade(Paint)
 }
-
-impl Upgrade {
-    fn render_self(&self, ctx: &mut Context, x: f64, y: f64) {
-        match self {
-            Upgrade::DamageUpgrade(cost, value) => {
-                ctx.draw(&Circle {
-                    x: x, 
-                    y: y,
-                    radius: 1.0,
-                    color: Color::Red
-                });
-                ctx.print(x - 4.0, y - 7.0, text::Line::from(vec!["Damage + ".into(), text::Span::from(value.to_string())]));
-                ctx.print(x - 1.0, y - 11.0, text::Line::from(vec![text::Span::from(cost.to_string()), " $".into()]));
-            },
-            Upgrade::FireRateUpgrade(cost, value) => {
-                ctx.draw(&Points {
-                    coords: &[(x, y), (x - 2.0, y), (x + 2.0, y)],
-                    color: Color::Red
-                });
-                ctx.print(x - 5.0, y - 5.0, text::Line::from(vec!["fire rate + ".into(), text::Span::from(value.to_string())]));
-                ctx.print(x - 1.0, y - 8.0, text::Line::from(vec![text::Span::from(cost.to_string()), " $".into()]));
-            },
-            Upgrade::RangeUpgrade(cost, value) => {
-                ctx.draw(&Circle {
-                    x: x,
-                    y: y,
-                    radius: 3.0,
-                    color: Color::White
-                });
-                ctx.print(x - 4.0, y - 10.0, text::Line::from(vec!["Range + ".into(), text::Span::from(value.to_string())]));
-                ctx.print(x - 1.0, y - 13.0, text::Line::from(vec![text::Span::from(cost.to_string()), " $".into()]));
-            },
-            Upgrade::ProjectileSpeedUpgrade(cost, value) => {
-                ctx.draw(&Circle {
-                    x: x,
-                    y: y,
-                    radius: 1.0,
-                    color: Color::Blue
-                });
-                ctx.print(x - 4.0, y - 10.0, text::Line::from(vec!["Speed + ".into(), text::Span::from(value.to_string())]));
-                ctx.print(x - 1.0, y - 13.0, text::Line::from(vec![text::Span::from(cost.to_string()), " $".into()]));
-            }
-        }
-    }
-}
\ No newline at end of file
@@ -1,4 +1,22 @@
 pub fn distance_in_2d(self_: Vec<f64>, target: Vec<f64>) -> f64 {
     //given that both represent coords in a 2d system:
     ((self_[0] - target[0]).powf(2.0) + (self_[1] - target[1]).powf(2.0)).sqrt()
-}
\ No newline at end of file
+}
+
+// current date as yyyy-mm-dd (utc), see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day:02}")
+}